@@ -23,11 +23,12 @@ use std::time::{Duration, Instant};
 use accessibility::{AXUIElement, AXUIElementActions, AXUIElementAttributes};
 use accessibility_sys::{
     kAXApplicationActivatedNotification, kAXApplicationDeactivatedNotification,
-    kAXErrorCannotComplete, kAXErrorNoValue, kAXErrorNotificationAlreadyRegistered,
-    kAXMainWindowChangedNotification, kAXStandardWindowSubrole, kAXTitleChangedNotification,
-    kAXUIElementDestroyedNotification, kAXWindowCreatedNotification,
-    kAXWindowDeminiaturizedNotification, kAXWindowMiniaturizedNotification,
-    kAXWindowMovedNotification, kAXWindowResizedNotification, kAXWindowRole,
+    kAXApplicationHiddenNotification, kAXApplicationShownNotification, kAXErrorCannotComplete,
+    kAXErrorNoValue, kAXErrorNotificationAlreadyRegistered, kAXMainWindowChangedNotification,
+    kAXStandardWindowSubrole, kAXTitleChangedNotification, kAXUIElementDestroyedNotification,
+    kAXWindowCreatedNotification, kAXWindowDeminiaturizedNotification,
+    kAXWindowMiniaturizedNotification, kAXWindowMovedNotification, kAXWindowResizedNotification,
+    kAXWindowRole,
 };
 use core_foundation::runloop::CFRunLoop;
 use core_foundation::string::CFString;
@@ -122,6 +123,14 @@ pub enum Request {
     /// marked `Quiet::Yes` automatically.
     Raise(Vec<WindowId>, CancellationToken, u64, Quiet),
 
+    /// Close the window by pressing its close button. The app may ask the
+    /// user to confirm first.
+    CloseWindow(WindowId),
+    /// Minimize the window into the Dock.
+    MinimizeWindow(WindowId),
+    /// Hide the app with the given pid, as if the user had pressed Cmd-H.
+    HideApp(pid_t),
+
     /// Sent by WindowServer actor when a window is destroyed.
     /// See [`actor::window_server::Request::RegisterWindow`].
     WindowDestroyed(WindowId),
@@ -177,6 +186,8 @@ struct WindowState {
 const APP_NOTIFICATIONS: &[&str] = &[
     kAXApplicationActivatedNotification,
     kAXApplicationDeactivatedNotification,
+    kAXApplicationHiddenNotification,
+    kAXApplicationShownNotification,
     kAXMainWindowChangedNotification,
     kAXWindowCreatedNotification,
 ];
@@ -539,6 +550,25 @@ impl State {
                     ))
                     .unwrap();
             }
+            &mut Request::CloseWindow(wid) => {
+                let window = self.window(wid)?;
+                let button = trace("close_button", &window.elem, || window.elem.close_button())?;
+                trace("press", &button, || button.press())?;
+                // The window destroyed notification takes it from here.
+            }
+            &mut Request::MinimizeWindow(wid) => {
+                let window = self.window(wid)?;
+                trace("set_minimized", &window.elem, || window.elem.set_minimized(true))?;
+                // The window miniaturized notification takes it from here.
+            }
+            &mut Request::HideApp(pid) => {
+                debug_assert_eq!(pid, self.pid);
+                // If this succeeds, the application hidden notification takes
+                // it from here.
+                if !self.running_app.hide() {
+                    warn!(?self.bundle_id, ?self.pid, "Failed to hide app");
+                }
+            }
             &mut Request::WindowDestroyed(wid) => {
                 self.on_window_destroyed(wid);
             }
@@ -555,6 +585,12 @@ impl State {
             kAXApplicationActivatedNotification | kAXApplicationDeactivatedNotification => {
                 _ = self.on_activation_changed();
             }
+            kAXApplicationHiddenNotification => {
+                self.send_event(Event::ApplicationHidden(self.pid));
+            }
+            kAXApplicationShownNotification => {
+                self.send_event(Event::ApplicationShown(self.pid));
+            }
             kAXMainWindowChangedNotification => {
                 self.on_main_window_changed(None);
             }
@@ -572,6 +608,7 @@ impl State {
                     window,
                     window_server_info,
                     event::get_mouse_state(),
+                    event::get_mouse_location(),
                 ));
             }
             kAXUIElementDestroyedNotification => {
@@ -600,9 +637,19 @@ impl State {
                     Some(event::get_mouse_state()),
                 ));
             }
-            // TODO: Handle all of these.
-            kAXWindowMiniaturizedNotification => {}
-            kAXWindowDeminiaturizedNotification => {}
+            kAXWindowMiniaturizedNotification => {
+                let Ok(wid) = self.id(&elem) else {
+                    return;
+                };
+                self.send_event(Event::WindowMinimized(wid));
+            }
+            kAXWindowDeminiaturizedNotification => {
+                let Ok(wid) = self.id(&elem) else {
+                    return;
+                };
+                self.send_event(Event::WindowDeminimized(wid));
+            }
+            // TODO: Handle this.
             kAXTitleChangedNotification => {}
             _ => {
                 error!("Unhandled notification {notif:?} on {elem:#?}");
@@ -816,6 +863,7 @@ impl State {
                     info,
                     window_server_info,
                     event::get_mouse_state(),
+                    event::get_mouse_location(),
                 ));
                 wid
             }
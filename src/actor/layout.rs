@@ -15,11 +15,16 @@ use tracing::{debug, error, warn};
 
 use crate::actor::app::{WindowId, pid_t};
 use crate::collections::{BTreeExt, BTreeSet, HashMap, HashSet};
-use crate::config::{Config, NewWindowPlacement, ScrollConfig};
+use crate::config::{
+    AutoGroup, Config, ModifierKeys, NewWindowPlacement, NewWindowWeight, ScrollConfig,
+    TreePlacement, WrapMode,
+};
+use crate::model::drop_zone::{DropZone, drop_zone};
+use crate::model::floating::Snap;
 use crate::model::scroll_viewport::ViewportState;
 use crate::model::{
-    ContainerKind, Direction, LayoutId, LayoutKind, LayoutTree, NodeId, Orientation,
-    SpaceLayoutMapping,
+    ContainerKind, Direction, InsertionPoint, LayoutId, LayoutKind, LayoutTree, NodeId,
+    Orientation, SpaceLayoutMapping, WindowSlot,
 };
 use crate::sys::geometry::{CGRectExt, CGSizeExt};
 use crate::sys::screen::SpaceId;
@@ -31,11 +36,39 @@ pub enum LayoutCommand {
     NextLayout,
     PrevLayout,
     MoveFocus(#[serde(rename = "direction")] Direction),
+    /// Focus the next window in the layout, in the order windows appear in
+    /// the tree.
+    FocusNext,
+    /// Focus the previous window in the layout, in the order windows appear
+    /// in the tree.
+    FocusPrev,
+    /// Focus the next tab of the group containing the selection.
+    NextTab,
+    /// Focus the previous tab of the group containing the selection.
+    PrevTab,
+    /// Focus the given tab of the group containing the selection, starting
+    /// from 1.
+    FocusTab(usize),
     Ascend,
     Descend,
     MoveNode(Direction),
+    /// Exchange the focused window with the window `move_focus` would select
+    /// in the given direction, leaving the shape of the tree untouched.
+    Swap(Direction),
+    /// Exchange the focused window with the first window in the layout.
+    SwapWithMaster,
     Split(Orientation),
+    /// Give every window under the selection an equal share of its parent.
+    Balance,
+    /// Flip the orientation of every container under the selection.
+    Rotate,
+    /// Reverse the order of windows under the selection in the given
+    /// orientation.
+    Mirror(Orientation),
     Group(Orientation),
+    /// Change the parent of the selection to an accordion.
+    Accordion(Orientation),
+    /// Change a group or accordion back to the kind of container it was.
     Ungroup,
     ToggleFocusFloating,
     ToggleWindowFloating,
@@ -45,16 +78,68 @@ pub enum LayoutCommand {
         direction: Direction,
         #[serde(default = "default_resize_percent")]
         percent: f64,
+        /// Resize by this many pixels instead of a percentage.
+        #[serde(default)]
+        px: Option<f64>,
+    },
+    /// Move the focused floating window.
+    Move {
+        direction: Direction,
+        #[serde(default = "default_move_px")]
+        px: f64,
     },
+    /// Snap the focused floating window to part of the screen.
+    Snap(Snap),
     CycleColumnWidth,
     ChangeLayoutKind,
     ToggleColumnTabbed,
+    /// Choose where the next window added to the space goes, relative to the
+    /// selection.
+    Presel(PreselArg),
 }
 
 fn default_resize_percent() -> f64 {
     5.0
 }
 
+fn default_move_px() -> f64 {
+    50.0
+}
+
+/// The argument to [`LayoutCommand::Presel`]: a direction, optionally with
+/// the share of the selection the new window takes, or "cancel".
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(untagged)]
+pub enum PreselArg {
+    Cancel(PreselCancel),
+    Direction(Direction),
+    WithRatio {
+        direction: Direction,
+        #[serde(default = "default_presel_ratio")]
+        ratio: f64,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PreselCancel {
+    Cancel,
+}
+
+fn default_presel_ratio() -> f64 {
+    0.5
+}
+
+/// Where the next window added to a space will go.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Presel {
+    pub layout: LayoutId,
+    pub node: NodeId,
+    pub direction: Direction,
+    /// The share of the node's space the new window takes.
+    pub ratio: f64,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum LayoutEvent {
     /// Used during restoration to make sure we don't retain windows for
@@ -63,8 +148,15 @@ pub enum LayoutEvent {
     AppClosed(pid_t),
     /// Updates the set of windows for a given app and space.
     WindowsOnScreenUpdated(SpaceId, pid_t, Vec<(WindowId, LayoutWindowInfo)>),
-    WindowAdded(SpaceId, WindowId, LayoutWindowInfo),
+    /// A window was created. Also has the tiled window under the mouse, if
+    /// new windows are placed under the cursor.
+    WindowAdded(SpaceId, WindowId, LayoutWindowInfo, Option<WindowId>),
     WindowRemoved(WindowId),
+    /// The windows were minimized or their app was hidden. They are taken out
+    /// of the layout until they are shown again.
+    WindowsHidden(Vec<WindowId>),
+    /// Puts hidden windows back where they were in the layout.
+    WindowsShown(Vec<WindowId>),
     WindowFocused(Vec<SpaceId>, WindowId),
     WindowResized {
         wid: WindowId,
@@ -104,16 +196,36 @@ impl LayoutCommand {
         use LayoutCommand::*;
         match self {
             MoveNode(_)
+            | Swap(_)
+            | SwapWithMaster
+            | Balance
+            | Rotate
+            | Mirror(_)
             | Group(_)
+            | Accordion(_)
             | Ungroup
             | Resize { .. }
             | CycleColumnWidth
             | ToggleColumnTabbed => true,
 
-            NextLayout | PrevLayout | MoveFocus(_) | Ascend | Descend | Split(_)
-            | ToggleFocusFloating | ToggleWindowFloating | ToggleFullscreen | ChangeLayoutKind => {
-                false
-            }
+            NextLayout
+            | PrevLayout
+            | MoveFocus(_)
+            | FocusNext
+            | FocusPrev
+            | NextTab
+            | PrevTab
+            | FocusTab(_)
+            | Ascend
+            | Descend
+            | Split(_)
+            | ToggleFocusFloating
+            | ToggleWindowFloating
+            | ToggleFullscreen
+            | ChangeLayoutKind
+            | Presel(_)
+            | Move { .. }
+            | Snap(_) => false,
         }
     }
 }
@@ -138,16 +250,35 @@ impl ResizeEdge {
     fn is_empty(self) -> bool {
         self.0 == 0
     }
+
+    /// Keeps only the edges whose direction satisfies `f`.
+    fn filter(self, f: impl Fn(Direction) -> bool) -> Self {
+        let edges = [
+            (Self::LEFT, Direction::Left),
+            (Self::RIGHT, Direction::Right),
+            (Self::TOP, Direction::Up),
+            (Self::BOTTOM, Direction::Down),
+        ];
+        ResizeEdge(
+            edges
+                .into_iter()
+                .filter(|&(bit, direction)| self.0 & bit != 0 && f(direction))
+                .fold(0, |acc, (bit, _)| acc | bit),
+        )
+    }
 }
 
-struct InteractiveScrollResize {
+struct InteractiveResize {
+    /// The node resized by horizontal drags. In scroll layouts this is the
+    /// column of `window_node`.
     column_node: NodeId,
     window_node: NodeId,
     edges: ResizeEdge,
     last_mouse: CGPoint,
+    is_scroll: bool,
 }
 
-struct InteractiveScrollMove {
+struct InteractiveMove {
     layout_id: LayoutId,
     window_id: WindowId,
     window_node: NodeId,
@@ -155,6 +286,14 @@ struct InteractiveScrollMove {
     drag_active: bool,
 }
 
+/// A window being dragged by its title bar in a tree layout, and where in the
+/// layout it would be placed if dropped now.
+struct InteractiveDrop {
+    layout_id: LayoutId,
+    window_id: WindowId,
+    target: Option<(WindowId, DropZone)>,
+}
+
 const RESIZE_EDGE_THRESHOLD: f64 = 8.0;
 const MOVE_DRAG_THRESHOLD: f64 = 10.0;
 
@@ -185,6 +324,14 @@ pub struct LayoutManager {
     tree: LayoutTree,
     layout_mapping: HashMap<SpaceId, SpaceLayoutMapping>,
     floating_windows: BTreeSet<WindowId>,
+    /// Windows that are hidden, along with where they were in each layout.
+    /// Kept in the order they were hidden.
+    #[serde(default)]
+    hidden_windows: Vec<(WindowId, Vec<WindowSlot>)>,
+    /// Where the next window added to each space goes, if chosen with
+    /// [`LayoutCommand::Presel`].
+    #[serde(default)]
+    presel: HashMap<SpaceId, Presel>,
     #[serde(skip)]
     active_floating_windows: HashMap<SpaceId, HashMap<pid_t, HashSet<WindowId>>>,
     #[serde(skip)]
@@ -202,9 +349,21 @@ pub struct LayoutManager {
     #[serde(skip)]
     scroll_enabled: bool,
     #[serde(skip)]
-    interactive_resize: Option<InteractiveScrollResize>,
+    auto_group: AutoGroup,
     #[serde(skip)]
-    interactive_move: Option<InteractiveScrollMove>,
+    new_window_placement: TreePlacement,
+    #[serde(skip)]
+    new_window_weight: NewWindowWeight,
+    #[serde(skip)]
+    focus_wrapping: WrapMode,
+    #[serde(skip)]
+    move_node_wrapping: WrapMode,
+    #[serde(skip)]
+    interactive_resize: Option<InteractiveResize>,
+    #[serde(skip)]
+    interactive_move: Option<InteractiveMove>,
+    #[serde(skip)]
+    interactive_drop: Option<InteractiveDrop>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -262,6 +421,8 @@ impl LayoutManager {
             tree: LayoutTree::new(),
             layout_mapping: Default::default(),
             floating_windows: Default::default(),
+            hidden_windows: Default::default(),
+            presel: Default::default(),
             active_floating_windows: Default::default(),
             focused_window: None,
             last_floating_focus: None,
@@ -269,14 +430,28 @@ impl LayoutManager {
             default_layout_kind: LayoutKind::default(),
             scroll_cfg: Config::default().settings.experimental.scroll.validated(),
             scroll_enabled: false,
+            auto_group: AutoGroup::default(),
+            new_window_placement: TreePlacement::default(),
+            new_window_weight: NewWindowWeight::default(),
+            focus_wrapping: WrapMode::default(),
+            move_node_wrapping: WrapMode::default(),
             interactive_resize: None,
             interactive_move: None,
+            interactive_drop: None,
         }
     }
 
     pub fn set_config(&mut self, config: &Config) {
         self.scroll_cfg = config.settings.experimental.scroll.clone().validated();
         self.scroll_enabled = self.scroll_cfg.enable;
+        self.auto_group = config.settings.auto_group.clone();
+        self.new_window_placement = config.settings.new_window_placement;
+        self.new_window_weight = config.settings.new_window_weight;
+        self.focus_wrapping = config.settings.focus_wrapping;
+        self.move_node_wrapping = config.settings.move_node_wrapping;
+        for vp in self.viewports.values_mut() {
+            vp.set_animation(self.scroll_cfg.animation);
+        }
         self.default_layout_kind = match (self.scroll_enabled, config.settings.default_layout_kind)
         {
             (false, LayoutKind::Scroll) => {
@@ -419,6 +594,9 @@ impl LayoutManager {
             let floating = floating.values().flatten().collect::<Vec<_>>();
             log!(print, "Floating {floating:?}");
         }
+        if let Some(presel) = self.presel.get(&space) {
+            log!(print, "Presel {presel:?}");
+        }
     }
 
     pub fn handle_event(&mut self, event: LayoutEvent) -> EventResponse {
@@ -449,6 +627,7 @@ impl LayoutManager {
                 floating_active.clear();
                 let mut add_floating = Vec::new();
                 let mut new_windows = Vec::new();
+                let mut new_tree_windows = Vec::new();
                 let tree_windows = windows
                     .iter()
                     .map(|(wid, _info)| *wid)
@@ -472,6 +651,7 @@ impl LayoutManager {
                                     new_windows.push(*wid);
                                     false
                                 } else {
+                                    new_tree_windows.push(*wid);
                                     true
                                 }
                             }
@@ -479,6 +659,10 @@ impl LayoutManager {
                     })
                     .collect();
                 self.tree.set_windows_for_app(self.layout(space), pid, tree_windows);
+                for wid in new_tree_windows {
+                    let bundle_id = window_map[&wid].bundle_id.as_deref();
+                    self.auto_group_window(layout, wid, bundle_id);
+                }
                 for wid in new_windows {
                     self.add_scroll_window(layout, wid);
                 }
@@ -488,12 +672,14 @@ impl LayoutManager {
             }
             LayoutEvent::AppsRunningUpdated(hash_set) => {
                 self.tree.retain_apps(|pid| hash_set.contains(&pid));
+                self.hidden_windows.retain(|(wid, _)| hash_set.contains(&wid.pid));
             }
             LayoutEvent::AppClosed(pid) => {
                 self.tree.remove_windows_for_app(pid);
                 self.floating_windows.remove_all_for_pid(pid);
+                self.hidden_windows.retain(|(wid, _)| wid.pid != pid);
             }
-            LayoutEvent::WindowAdded(space, wid, info) => {
+            LayoutEvent::WindowAdded(space, wid, info, under_cursor) => {
                 self.debug_tree(space);
                 match classify_window(&info) {
                     WindowClass::FloatByDefault => self.add_floating_window(wid, Some(space)),
@@ -501,8 +687,18 @@ impl LayoutManager {
                         let layout = self.layout(space);
                         if self.tree.is_scroll_layout(layout) {
                             self.add_scroll_window(layout, wid);
+                        } else if let Some(presel) = self.take_presel(space) {
+                            self.tree.add_window_beside(
+                                layout,
+                                presel.node,
+                                wid,
+                                presel.direction,
+                                presel.ratio,
+                            );
                         } else {
-                            self.tree.add_window_after(layout, self.tree.selection(layout), wid);
+                            let point = self.insertion_point(layout, under_cursor);
+                            self.tree.add_window_at(layout, point, wid, self.new_window_weight);
+                            self.auto_group_window(layout, wid, info.bundle_id.as_deref());
                         }
                     }
                     WindowClass::Untracked => (),
@@ -511,6 +707,26 @@ impl LayoutManager {
             LayoutEvent::WindowRemoved(wid) => {
                 self.tree.remove_window(wid);
                 self.floating_windows.remove(&wid);
+                self.hidden_windows.retain(|(hidden, _)| *hidden != wid);
+            }
+            LayoutEvent::WindowsHidden(wids) => {
+                for wid in wids {
+                    let slots = self.tree.detach_window(wid);
+                    if !slots.is_empty() {
+                        self.hidden_windows.retain(|(hidden, _)| *hidden != wid);
+                        self.hidden_windows.push((wid, slots));
+                    }
+                }
+            }
+            LayoutEvent::WindowsShown(wids) => {
+                // Restore in the reverse order windows were hidden, so each one
+                // sees the same neighbours it had when it was hidden.
+                for idx in (0..self.hidden_windows.len()).rev() {
+                    if wids.contains(&self.hidden_windows[idx].0) {
+                        let (wid, slots) = self.hidden_windows.remove(idx);
+                        self.restore_hidden_window(wid, &slots);
+                    }
+                }
             }
             LayoutEvent::WindowFocused(spaces, wid) => {
                 self.focused_window = Some(wid);
@@ -741,10 +957,15 @@ impl LayoutManager {
                         self.tree.selection(layout),
                         direction,
                     )
+                } else if self.focus_wrapping == WrapMode::Workspace && !is_scroll {
+                    self.tree.traverse_wrapping(self.tree.selection(layout), direction)
                 } else {
                     self.tree.traverse(self.tree.selection(layout), direction)
                 }
                 .or_else(|| {
+                    if !is_scroll && self.focus_wrapping != WrapMode::Screen {
+                        return None;
+                    }
                     let layout = self.layout(next_space(direction)?);
                     Some(self.tree.selection(layout))
                 });
@@ -757,6 +978,39 @@ impl LayoutManager {
                     .unwrap_or_default();
                 EventResponse { focus_window, raise_windows }
             }
+            LayoutCommand::FocusNext | LayoutCommand::FocusPrev => {
+                let step = if let LayoutCommand::FocusNext = command {
+                    1
+                } else {
+                    -1
+                };
+                let selection = self.tree.selection(layout);
+                let Some(target) = self.tree.cycle_windows(layout, selection, step) else {
+                    return EventResponse::default();
+                };
+                let wid = self.tree.window_at(target).unwrap();
+                self.focus_window(space, wid)
+            }
+            LayoutCommand::NextTab | LayoutCommand::PrevTab | LayoutCommand::FocusTab(_) => {
+                let selection = self.tree.selection(layout);
+                let Some((group, idx)) = self.tree.enclosing_group(selection) else {
+                    return EventResponse::default();
+                };
+                let tabs: Vec<_> = group.children(self.tree.map()).collect();
+                let new_idx = match command {
+                    LayoutCommand::NextTab => (idx + 1) % tabs.len(),
+                    LayoutCommand::PrevTab => (idx + tabs.len() - 1) % tabs.len(),
+                    LayoutCommand::FocusTab(n) => n.wrapping_sub(1),
+                    _ => unreachable!(),
+                };
+                let Some(&tab) = tabs.get(new_idx).filter(|_| new_idx != idx) else {
+                    return EventResponse::default();
+                };
+                let Some(wid) = self.tree.window_at(self.tree.selected_leaf(tab)) else {
+                    return EventResponse::default();
+                };
+                self.focus_window(space, wid)
+            }
             LayoutCommand::Ascend => {
                 self.tree.ascend_selection(layout);
                 EventResponse::default()
@@ -767,7 +1021,13 @@ impl LayoutManager {
             }
             LayoutCommand::MoveNode(direction) => {
                 let selection = self.tree.selection(layout);
-                if !self.tree.move_node(layout, selection, direction) {
+                let is_scroll = self.tree.is_scroll_layout(layout);
+                let moved = if self.move_node_wrapping == WrapMode::Workspace && !is_scroll {
+                    self.tree.move_node_wrapping(layout, selection, direction)
+                } else {
+                    self.tree.move_node(layout, selection, direction)
+                };
+                if !moved && (is_scroll || self.move_node_wrapping == WrapMode::Screen) {
                     if let Some(new_space) = next_space(direction) {
                         let new_layout = self.layout(new_space);
                         self.tree.move_node_after(self.tree.selection(new_layout), selection);
@@ -775,6 +1035,25 @@ impl LayoutManager {
                 }
                 EventResponse::default()
             }
+            LayoutCommand::Swap(direction) => {
+                let selection = self.tree.selection(layout);
+                let Some(target) = self.tree.traverse(selection, direction) else {
+                    return EventResponse::default();
+                };
+                self.swap_selection_with(selection, target)
+            }
+            LayoutCommand::SwapWithMaster => {
+                let selection = self.tree.selection(layout);
+                let map = self.tree.map();
+                let master =
+                    std::iter::successors(Some(self.tree.root(layout)), |n| n.first_child(map))
+                        .last()
+                        .unwrap();
+                if master == selection {
+                    return EventResponse::default();
+                }
+                self.swap_selection_with(selection, master)
+            }
             LayoutCommand::Split(orientation) => {
                 // Don't mark as written yet, since merely splitting doesn't
                 // usually have a visible effect.
@@ -782,15 +1061,39 @@ impl LayoutManager {
                 self.tree.nest_in_container(layout, selection, ContainerKind::from(orientation));
                 EventResponse::default()
             }
+            LayoutCommand::Balance => {
+                if let Some(node) = self.transform_target(layout) {
+                    self.tree.balance(node);
+                }
+                EventResponse::default()
+            }
+            LayoutCommand::Rotate => {
+                if let Some(node) = self.transform_target(layout) {
+                    self.tree.rotate(node);
+                }
+                EventResponse::default()
+            }
+            LayoutCommand::Mirror(orientation) => {
+                if let Some(node) = self.transform_target(layout) {
+                    self.tree.mirror(node, orientation);
+                }
+                EventResponse::default()
+            }
             LayoutCommand::Group(orientation) => {
                 if let Some(parent) = self.tree.selection(layout).parent(self.tree.map()) {
                     self.tree.set_container_kind(parent, ContainerKind::group(orientation));
                 }
                 EventResponse::default()
             }
+            LayoutCommand::Accordion(orientation) => {
+                if let Some(parent) = self.tree.selection(layout).parent(self.tree.map()) {
+                    self.tree.set_container_kind(parent, ContainerKind::accordion(orientation));
+                }
+                EventResponse::default()
+            }
             LayoutCommand::Ungroup => {
                 if let Some(parent) = self.tree.selection(layout).parent(self.tree.map()) {
-                    if self.tree.container_kind(parent).is_group() {
+                    if !self.tree.container_kind(parent).is_split() {
                         self.tree.set_container_kind(
                             parent,
                             self.tree.last_ungrouped_container_kind(parent),
@@ -818,7 +1121,9 @@ impl LayoutManager {
                     EventResponse::default()
                 }
             }
-            LayoutCommand::Resize { direction, percent } => {
+            // These only apply to floating windows, which the reactor moves.
+            LayoutCommand::Move { .. } | LayoutCommand::Snap(_) => EventResponse::default(),
+            LayoutCommand::Resize { direction, percent, .. } => {
                 let percent = percent.clamp(-100.0, 100.0);
                 let node = self.tree.selection(layout);
                 self.tree.resize(node, percent / 100.0, direction);
@@ -863,6 +1168,27 @@ impl LayoutManager {
                 }
                 EventResponse::default()
             }
+            LayoutCommand::Presel(arg) => {
+                let (direction, ratio) = match arg {
+                    PreselArg::Cancel(_) => {
+                        self.presel.remove(&space);
+                        return EventResponse::default();
+                    }
+                    PreselArg::Direction(direction) => (direction, default_presel_ratio()),
+                    PreselArg::WithRatio { direction, ratio } => (direction, ratio),
+                };
+                if self.tree.is_scroll_layout(layout) {
+                    return EventResponse::default();
+                }
+                let presel = Presel {
+                    layout,
+                    node: self.tree.selection(layout),
+                    direction,
+                    ratio: ratio.clamp(0.1, 0.9),
+                };
+                self.presel.insert(space, presel);
+                EventResponse::default()
+            }
             LayoutCommand::ChangeLayoutKind => {
                 let old_kind = self.tree.layout_kind(layout);
                 let new_kind = match old_kind {
@@ -883,6 +1209,93 @@ impl LayoutManager {
         }
     }
 
+    /// Focuses the window that should take over from `wid` when the windows
+    /// matching `leaving` are about to disappear from the layout.
+    ///
+    /// This is the first remaining window that `traverse` reaches from `wid`,
+    /// trying the directions along its parent container first so that focus
+    /// stays with a sibling when there is one.
+    pub fn focus_neighbor(
+        &mut self,
+        space: SpaceId,
+        wid: WindowId,
+        leaving: impl Fn(WindowId) -> bool,
+    ) -> EventResponse {
+        let Some(layout) = self.try_layout(space) else {
+            return EventResponse::default();
+        };
+        let Some(node) = self.tree.window_node(layout, wid) else {
+            return EventResponse::default();
+        };
+        use Direction::*;
+        let orientation = node
+            .parent(self.tree.map())
+            .map(|parent| self.tree.container_kind(parent).orientation());
+        let directions = match orientation {
+            Some(Orientation::Vertical) => [Up, Down, Left, Right],
+            _ => [Left, Right, Up, Down],
+        };
+        let target = directions.into_iter().find_map(|direction| {
+            std::iter::successors(self.tree.traverse(node, direction), |&n| {
+                self.tree.traverse(n, direction)
+            })
+            .find(|&n| self.tree.window_at(n).is_some_and(|wid| !leaving(wid)))
+        });
+        let Some(target) = target else {
+            return EventResponse::default();
+        };
+        EventResponse {
+            raise_windows: self.tree.select_returning_surfaced_windows(target),
+            focus_window: self.tree.window_at(target),
+        }
+    }
+
+    fn restore_hidden_window(&mut self, wid: WindowId, slots: &[WindowSlot]) {
+        for slot in slots {
+            if self.tree.restore_window(wid, slot).is_some() {
+                continue;
+            }
+            // The window's old spot is gone. Add it the same way as a new
+            // window, as long as the layout still exists.
+            let Some(layout) = self.tree.layouts().find(|&l| l == slot.layout()) else {
+                continue;
+            };
+            if self.tree.is_scroll_layout(layout) {
+                self.add_scroll_window(layout, wid);
+            } else {
+                self.tree.add_window_after(layout, self.tree.selection(layout), wid);
+            }
+        }
+    }
+
+    /// Picks the node that balance, rotate and mirror apply to. When a window
+    /// is selected, this is the container it is in.
+    fn transform_target(&self, layout: LayoutId) -> Option<NodeId> {
+        if self.tree.is_scroll_layout(layout) {
+            return None;
+        }
+        let selection = self.tree.selection(layout);
+        match selection.parent(self.tree.map()) {
+            Some(parent) if selection.is_empty(self.tree.map()) => Some(parent),
+            _ => Some(selection),
+        }
+    }
+
+    /// Swaps the windows at `selection` and `target`, moving the selection
+    /// along with the selected window.
+    fn swap_selection_with(&mut self, selection: NodeId, target: NodeId) -> EventResponse {
+        let (Some(wid), Some(_)) = (self.tree.window_at(selection), self.tree.window_at(target))
+        else {
+            return EventResponse::default();
+        };
+        self.tree.swap_windows(selection, target);
+        let raise_windows = self.tree.select_returning_surfaced_windows(target);
+        EventResponse {
+            raise_windows,
+            focus_window: Some(wid),
+        }
+    }
+
     fn is_floating(&self) -> bool {
         if let Some(focus) = self.focused_window {
             self.floating_windows.contains(&focus)
@@ -919,6 +1332,106 @@ impl LayoutManager {
         self.floating_windows.remove(&wid);
     }
 
+    pub fn is_floating_window(&self, wid: WindowId) -> bool {
+        self.floating_windows.contains(&wid)
+    }
+
+    /// Whether the window is tiled in the active layout of a space or is
+    /// floating.
+    pub fn is_window_managed(&self, wid: WindowId) -> bool {
+        self.floating_windows.contains(&wid)
+            || self
+                .layout_mapping
+                .values()
+                .any(|mapping| self.tree.window_node(mapping.active_layout(), wid).is_some())
+    }
+
+    /// Returns the frames of the tiled windows in `space` that can be seen on
+    /// `screen`. Unlike [`Self::calculate_layout`], this leaves out windows
+    /// hidden behind others in a group and scrolled out of view.
+    pub fn visible_window_frames(
+        &self,
+        space: SpaceId,
+        screen: CGRect,
+        config: &Config,
+    ) -> Vec<(WindowId, CGRect)> {
+        let layout = self.layout(space);
+        let visible: HashSet<_> =
+            self.tree.visible_windows_under(self.tree.root(layout)).into_iter().collect();
+        let mut frames = self.calculate_layout(space, screen, config);
+        frames.retain(|(wid, frame)| {
+            visible.contains(wid) && frame.intersection(&screen).area() > 0.0
+        });
+        frames
+    }
+
+    /// Whether the active layout of `space` scrolls.
+    pub fn is_scroll_layout(&self, space: SpaceId) -> bool {
+        self.try_layout(space).is_some_and(|layout| self.tree.is_scroll_layout(layout))
+    }
+
+    /// Returns the floating windows in `space`.
+    pub fn floating_windows_in(&self, space: SpaceId) -> impl Iterator<Item = WindowId> + '_ {
+        self.active_floating_windows
+            .get(&space)
+            .into_iter()
+            .flat_map(|windows| windows.values().flatten().copied())
+    }
+
+    /// Focuses `wid`, selecting it if it is tiled in `space`.
+    pub fn focus_window(&mut self, space: SpaceId, wid: WindowId) -> EventResponse {
+        let Some(layout) = self.try_layout(space) else {
+            return EventResponse::default();
+        };
+        let Some(node) = self.tree.window_node(layout, wid) else {
+            return EventResponse {
+                raise_windows: vec![],
+                focus_window: Some(wid),
+            };
+        };
+        if self.tree.is_scroll_layout(layout) {
+            self.clear_user_scrolling(space);
+        }
+        EventResponse {
+            raise_windows: self.tree.select_returning_surfaced_windows(node),
+            focus_window: Some(wid),
+        }
+    }
+
+    /// Moves the selection in `from` to `to`, after the selection there, and
+    /// keeps it focused.
+    pub fn move_selection_to_space(&mut self, from: SpaceId, to: SpaceId) -> EventResponse {
+        if from == to || self.is_floating() {
+            return EventResponse::default();
+        }
+        for space in [from, to] {
+            let Some(mapping) = self.layout_mapping.get_mut(&space) else {
+                return EventResponse::default();
+            };
+            mapping.prepare_modify(&mut self.tree);
+        }
+        let selection = self.tree.selection(self.layout(from));
+        let target = self.tree.selection(self.layout(to));
+        self.tree.move_node_after(target, selection);
+        self.tree.select(selection);
+        EventResponse {
+            raise_windows: vec![],
+            focus_window: self.tree.window_at(selection),
+        }
+    }
+
+    /// Focuses the selected window in `space`.
+    pub fn focus_space(&mut self, space: SpaceId) -> EventResponse {
+        let Some(layout) = self.try_layout(space) else {
+            return EventResponse::default();
+        };
+        let target = self.tree.selected_leaf(self.tree.selection(layout));
+        EventResponse {
+            raise_windows: self.tree.select_returning_surfaced_windows(target),
+            focus_window: self.tree.window_at(target),
+        }
+    }
+
     pub fn calculate_layout(
         &self,
         space: SpaceId,
@@ -976,12 +1489,74 @@ impl LayoutManager {
         );
     }
 
+    /// Where to add a new window to a tree layout, according to the
+    /// configured placement.
+    fn insertion_point(&self, layout: LayoutId, under_cursor: Option<WindowId>) -> InsertionPoint {
+        let selection = self.tree.selection(layout);
+        let root = self.tree.root(layout);
+        let next_to = |node: Option<NodeId>| InsertionPoint::After(node.unwrap_or(selection));
+        match self.new_window_placement {
+            TreePlacement::AfterFocused => InsertionPoint::After(selection),
+            TreePlacement::BeforeFocused => InsertionPoint::Before(selection),
+            TreePlacement::EndOfParent => {
+                InsertionPoint::End(selection.parent(self.tree.map()).unwrap_or(root))
+            }
+            TreePlacement::RootEnd => InsertionPoint::End(root),
+            TreePlacement::UnderCursor => {
+                next_to(under_cursor.and_then(|wid| self.tree.window_node(layout, wid)))
+            }
+            TreePlacement::LargestTile => next_to(self.tree.largest_window(layout)),
+        }
+    }
+
+    /// Removes the presel for `space`, returning it if it still points to a
+    /// node in the active layout.
+    fn take_presel(&mut self, space: SpaceId) -> Option<Presel> {
+        let presel = self.presel.remove(&space)?;
+        let layout = self.layout(space);
+        let map = self.tree.map();
+        let is_valid = presel.layout == layout
+            && map.contains(presel.node)
+            && presel.node.ancestors(map).last() == Some(self.tree.root(layout));
+        is_valid.then_some(presel)
+    }
+
+    /// Groups a newly added window with another window of its app, if
+    /// auto-grouping is enabled for the app.
+    fn auto_group_window(&mut self, layout: LayoutId, wid: WindowId, bundle_id: Option<&str>) {
+        if !self.auto_group.is_enabled_for(bundle_id) {
+            return;
+        }
+        let Some(node) = self.tree.window_node(layout, wid) else {
+            return;
+        };
+        // Prefer the focused window, since that's probably where the new
+        // window came from.
+        let root = self.tree.root(layout);
+        let anchor = self
+            .focused_window
+            .filter(|focused| focused.pid == wid.pid && *focused != wid)
+            .and_then(|focused| self.tree.window_node(layout, focused))
+            .or_else(|| {
+                root.traverse_preorder(self.tree.map()).find(|&other| {
+                    self.tree.window_at(other).is_some_and(|w| w.pid == wid.pid && w != wid)
+                })
+            });
+        if let Some(anchor) = anchor {
+            self.tree.group_with(layout, node, anchor);
+        }
+    }
+
     pub fn viewport(&self, layout: LayoutId) -> Option<&ViewportState> {
         self.viewports.get(&layout)
     }
 
     pub fn viewport_mut(&mut self, layout: LayoutId, screen_width: f64) -> &mut ViewportState {
-        self.viewports.entry(layout).or_insert_with(|| ViewportState::new(screen_width))
+        self.viewports.entry(layout).or_insert_with(|| {
+            let mut vp = ViewportState::new(screen_width);
+            vp.set_animation(self.scroll_cfg.animation);
+            vp
+        })
     }
 
     pub fn clear_user_scrolling(&mut self, space: SpaceId) {
@@ -1073,19 +1648,15 @@ impl LayoutManager {
         } else {
             delta_x
         };
-        let scaled_delta = delta * config.scroll_sensitivity;
-
-        let is_discrete = delta_x.abs() < 10.0 && delta_x.fract() == 0.0;
-        let (effective_delta, effective_threshold) = if is_discrete {
-            (scaled_delta.signum() * step_threshold, step_threshold)
-        } else {
-            (scaled_delta, step_threshold)
-        };
 
         let vp = self.viewport_mut(layout, screen.size.width);
         vp.set_screen_width(screen.size.width);
 
-        let steps = match vp.accumulate_scroll(effective_delta, effective_threshold) {
+        let steps = match vp.scroll_progress.accumulate_event(
+            delta,
+            config.scroll_sensitivity,
+            step_threshold,
+        ) {
             Some(s) => s,
             None => return EventResponse::default(),
         };
@@ -1121,44 +1692,59 @@ impl LayoutManager {
         EventResponse { focus_window, raise_windows }
     }
 
-    pub(crate) fn hit_test_scroll_edges(
+    /// Finds a window edge under `point` that can be dragged to resize the
+    /// layout.
+    pub(crate) fn hit_test_edges(
         &self,
         space: SpaceId,
         point: CGPoint,
         screen: CGRect,
         config: &Config,
     ) -> Option<(NodeId, NodeId, ResizeEdge)> {
-        if !self.scroll_enabled {
-            return None;
-        }
         let layout = self.try_layout(space)?;
-        if !self.tree.is_scroll_layout(layout) {
+        let is_scroll = self.tree.is_scroll_layout(layout);
+        if is_scroll && !self.scroll_enabled {
             return None;
         }
         let frames = self.calculate_layout(space, screen, config);
         for (wid, frame) in &frames {
             let edges = detect_edges(point, *frame);
-            if !edges.is_empty() {
-                let window_node = self.tree.window_node(layout, *wid)?;
+            if edges.is_empty() {
+                continue;
+            }
+            let window_node = self.tree.window_node(layout, *wid)?;
+            if is_scroll {
                 let column_node = self.tree.column_of(layout, window_node)?;
                 return Some((column_node, window_node, edges));
             }
+            // Only borders shared with another tile can be dragged.
+            let edges = edges.filter(|direction| self.tree.can_resize(window_node, direction));
+            if !edges.is_empty() {
+                return Some((window_node, window_node, edges));
+            }
         }
         None
     }
 
-    pub fn hit_test_scroll_window(
+    /// Finds the window under `point` that can be dragged onto another to
+    /// swap them.
+    ///
+    /// In tree layouts this requires holding the configured modifier keys,
+    /// since plain drags move the window itself.
+    pub fn hit_test_window(
         &self,
         space: SpaceId,
         point: CGPoint,
         screen: CGRect,
+        modifiers: ModifierKeys,
         config: &Config,
     ) -> Option<(WindowId, NodeId)> {
-        if !self.scroll_enabled {
-            return None;
-        }
         let layout = self.try_layout(space)?;
-        if !self.tree.is_scroll_layout(layout) {
+        if self.tree.is_scroll_layout(layout) {
+            if !self.scroll_enabled {
+                return None;
+            }
+        } else if !modifiers.contains(config.settings.drag_swap_modifiers) {
             return None;
         }
         let frames = self.calculate_layout(space, screen, config);
@@ -1173,6 +1759,7 @@ impl LayoutManager {
 
     pub(crate) fn begin_interactive_resize(
         &mut self,
+        space: SpaceId,
         column: NodeId,
         window: NodeId,
         edges: ResizeEdge,
@@ -1181,11 +1768,28 @@ impl LayoutManager {
         if self.interactive_resize.is_some() {
             return false;
         }
-        self.interactive_resize = Some(InteractiveScrollResize {
+        let Some(mapping) = self.layout_mapping.get_mut(&space) else {
+            return false;
+        };
+        let is_scroll = self.tree.is_scroll_layout(mapping.active_layout());
+        let (mut column, mut window) = (column, window);
+        if !is_scroll {
+            // This can clone the layout, so look up the node afterward.
+            let Some(wid) = self.tree.window_at(window) else {
+                return false;
+            };
+            let layout = mapping.prepare_modify(&mut self.tree);
+            let Some(node) = self.tree.window_node(layout, wid) else {
+                return false;
+            };
+            (column, window) = (node, node);
+        }
+        self.interactive_resize = Some(InteractiveResize {
             column_node: column,
             window_node: window,
             edges,
             last_mouse: mouse,
+            is_scroll,
         });
         true
     }
@@ -1198,26 +1802,34 @@ impl LayoutManager {
         let dy = mouse.y - state.last_mouse.y;
         state.last_mouse = mouse;
 
+        // Tree layouts grow a node toward the direction it is resized in, so
+        // dragging a left or top edge outward is a positive change.
+        let is_scroll = state.is_scroll;
+        let signed = |delta: f64, direction: Direction| match direction {
+            Direction::Left | Direction::Up if !is_scroll => -delta,
+            _ => delta,
+        };
+
         let mut changed = false;
         if state.edges.has_horizontal() {
-            let ratio = dx / screen.size.width;
             let direction = if state.edges.0 & ResizeEdge::LEFT != 0 {
                 Direction::Left
             } else {
                 Direction::Right
             };
+            let ratio = signed(dx, direction) / screen.size.width;
             let col = state.column_node;
             if self.tree.resize(col, ratio, direction) {
                 changed = true;
             }
         }
         if state.edges.has_vertical() {
-            let ratio = dy / screen.size.height;
             let direction = if state.edges.0 & ResizeEdge::TOP != 0 {
                 Direction::Up
             } else {
                 Direction::Down
             };
+            let ratio = signed(dy, direction) / screen.size.height;
             let win = state.window_node;
             if self.tree.resize(win, ratio, direction) {
                 changed = true;
@@ -1243,8 +1855,20 @@ impl LayoutManager {
         if self.interactive_resize.is_some() || self.interactive_move.is_some() {
             return false;
         }
-        let layout_id = self.layout(space);
-        self.interactive_move = Some(InteractiveScrollMove {
+        let Some(mapping) = self.layout_mapping.get_mut(&space) else {
+            return false;
+        };
+        let mut layout_id = mapping.active_layout();
+        let mut node = node;
+        if !self.tree.is_scroll_layout(layout_id) {
+            // This can clone the layout, so look up the node afterward.
+            layout_id = mapping.prepare_modify(&mut self.tree);
+            let Some(new_node) = self.tree.window_node(layout_id, wid) else {
+                return false;
+            };
+            node = new_node;
+        }
+        self.interactive_move = Some(InteractiveMove {
             layout_id,
             window_id: wid,
             window_node: node,
@@ -1312,9 +1936,85 @@ impl LayoutManager {
         }
     }
 
+    /// Starts tracking a window that may be dragged onto another tile.
+    ///
+    /// Unlike the other interactive states, the window itself is moved by the
+    /// system during the drag and the layout only changes once it is dropped.
+    pub fn begin_interactive_drop(
+        &mut self,
+        space: SpaceId,
+        point: CGPoint,
+        screen: CGRect,
+        config: &Config,
+    ) -> bool {
+        let Some(layout_id) = self.try_layout(space) else {
+            return false;
+        };
+        if self.tree.is_scroll_layout(layout_id) || self.has_interactive_state() {
+            return false;
+        }
+        let frames = self.tree.calculate_layout(layout_id, screen, config);
+        let Some(&(window_id, _)) = frames.iter().find(|(_, frame)| frame.contains(point)) else {
+            return false;
+        };
+        self.interactive_drop = Some(InteractiveDrop {
+            layout_id,
+            window_id,
+            target: None,
+        });
+        true
+    }
+
+    pub fn update_interactive_drop(&mut self, mouse: CGPoint, screen: CGRect, config: &Config) {
+        let Some(state) = self.interactive_drop.as_ref() else {
+            return;
+        };
+        let frames = self.tree.calculate_layout(state.layout_id, screen, config);
+        let target = frames
+            .iter()
+            .filter(|(wid, _)| *wid != state.window_id)
+            .find_map(|&(wid, frame)| Some((wid, drop_zone(frame, mouse)?)));
+        if let Some(state) = self.interactive_drop.as_mut() {
+            state.target = target;
+        }
+    }
+
+    /// Moves the dragged window to where it was dropped, if it was dropped on
+    /// another tile. Returns true if the layout changed.
+    ///
+    /// `dragged` is the window the system reported moving during the drag,
+    /// which guards against clicks that didn't move the window under the mouse.
+    pub fn end_interactive_drop(&mut self, space: SpaceId, dragged: Option<WindowId>) -> bool {
+        let Some(state) = self.interactive_drop.take() else {
+            return false;
+        };
+        let Some((target_wid, zone)) = state.target else {
+            return false;
+        };
+        if dragged != Some(state.window_id) {
+            return false;
+        }
+        let Some(mapping) = self.layout_mapping.get_mut(&space) else {
+            return false;
+        };
+        if mapping.active_layout() != state.layout_id {
+            return false;
+        }
+        // This can clone the layout, so look up the nodes afterward.
+        let layout = mapping.prepare_modify(&mut self.tree);
+        let (Some(node), Some(target)) = (
+            self.tree.window_node(layout, state.window_id),
+            self.tree.window_node(layout, target_wid),
+        ) else {
+            return false;
+        };
+        self.tree.drop_node(layout, node, target, zone)
+    }
+
     pub fn cancel_interactive_state(&mut self) {
         self.interactive_resize = None;
         self.interactive_move = None;
+        self.interactive_drop = None;
     }
 
     pub fn has_interactive_state(&self) -> bool {
@@ -1359,6 +2059,11 @@ impl LayoutManager {
     pub(super) fn active_layout_kind(&self, space: SpaceId) -> LayoutKind {
         self.tree.layout_kind(self.layout(space))
     }
+
+    #[cfg(test)]
+    fn presel(&self, space: SpaceId) -> Option<&Presel> {
+        self.presel.get(&space)
+    }
 }
 
 // TODO: detect_edges does not account for screen boundaries.
@@ -1786,7 +2491,7 @@ mod tests {
 
         // Add a new window when the left window is selected.
         _ = mgr.handle_event(WindowFocused(vec![space], WindowId::new(pid, 1)));
-        _ = mgr.handle_event(WindowAdded(space, WindowId::new(pid, 6), win_info()));
+        _ = mgr.handle_event(WindowAdded(space, WindowId::new(pid, 6), win_info(), None));
         assert_eq!(
             vec![
                 (WindowId::new(pid, 1), rect(0, 0, 75, 30)),
@@ -1801,7 +2506,7 @@ mod tests {
 
         // Add a new window when the top middle is selected.
         _ = mgr.handle_event(WindowFocused(vec![space], WindowId::new(pid, 2)));
-        _ = mgr.handle_event(WindowAdded(space, WindowId::new(pid, 6), win_info()));
+        _ = mgr.handle_event(WindowAdded(space, WindowId::new(pid, 6), win_info(), None));
         assert_eq!(
             vec![
                 (WindowId::new(pid, 1), rect(0, 0, 100, 30)),
@@ -1832,7 +2537,7 @@ mod tests {
 
         // Add a new window when the bottom middle is selected.
         _ = mgr.handle_event(WindowFocused(vec![space], WindowId::new(pid, 3)));
-        _ = mgr.handle_event(WindowAdded(space, WindowId::new(pid, 6), win_info()));
+        _ = mgr.handle_event(WindowAdded(space, WindowId::new(pid, 6), win_info(), None));
         assert_eq!(
             vec![
                 (WindowId::new(pid, 1), rect(0, 0, 100, 30)),
@@ -1847,7 +2552,7 @@ mod tests {
 
         // Add a new window when the right window is selected.
         _ = mgr.handle_event(WindowFocused(vec![space], WindowId::new(pid, 4)));
-        _ = mgr.handle_event(WindowAdded(space, WindowId::new(pid, 6), win_info()));
+        _ = mgr.handle_event(WindowAdded(space, WindowId::new(pid, 6), win_info(), None));
         assert_eq!(
             vec![
                 (WindowId::new(pid, 1), rect(0, 0, 75, 30)),
@@ -1862,18 +2567,247 @@ mod tests {
     }
 
     #[test]
-    fn add_remove_add() {
+    fn new_windows_are_grouped_with_their_app() {
         use LayoutEvent::*;
         let mut mgr = LayoutManager::new();
+        let mut config = Config::default();
+        config.settings.auto_group.apps = vec!["com.example.Terminal".to_string()];
+        mgr.set_config(&config);
         let space = SpaceId::new(1);
-        let pid = 1;
+        let term = |idx| {
+            let info = LayoutWindowInfo {
+                bundle_id: Some("com.example.Terminal".to_string()),
+                ..win_info()
+            };
+            (WindowId::new(1, idx), info)
+        };
+
+        let screen = rect(0, 0, 300, 100);
+        _ = mgr.handle_event(SpaceExposed(space, screen.size));
+        _ = mgr.handle_event(WindowsOnScreenUpdated(space, 2, make_windows(2, 1)));
+        _ = mgr.handle_event(WindowsOnScreenUpdated(space, 1, vec![term(1)]));
+        assert_eq!(
+            vec![
+                (WindowId::new(1, 1), rect(150, 0, 150, 100)),
+                (WindowId::new(2, 1), rect(0, 0, 150, 100)),
+            ],
+            mgr.layout_sorted(space, screen),
+        );
+
+        // A group is created for the second window.
+        _ = mgr.handle_event(WindowFocused(vec![space], WindowId::new(1, 1)));
+        let (wid, info) = term(2);
+        _ = mgr.handle_event(WindowAdded(space, wid, info, None));
+        _ = mgr.handle_event(WindowFocused(vec![space], wid));
+        let layout = mgr.layout(space);
+        let node = mgr.tree.window_node(layout, wid).unwrap();
+        let group = node.parent(mgr.tree.map()).unwrap();
+        assert_eq!(ContainerKind::Tabbed, mgr.tree.container_kind(group));
+
+        // Later windows join it.
+        _ = mgr.handle_event(WindowsOnScreenUpdated(space, 1, vec![term(1), term(2), term(3)]));
+        assert_eq!(
+            vec![
+                (WindowId::new(1, 1), rect(150, 6, 150, 94)),
+                (WindowId::new(1, 2), rect(150, 6, 150, 94)),
+                (WindowId::new(1, 3), rect(150, 6, 150, 94)),
+                (WindowId::new(2, 1), rect(0, 0, 150, 100)),
+            ],
+            mgr.layout_sorted(space, screen),
+        );
+
+        // Other apps are left alone.
+        _ = mgr.handle_event(WindowFocused(vec![space], WindowId::new(2, 1)));
+        _ = mgr.handle_event(WindowAdded(space, WindowId::new(2, 2), win_info(), None));
+        assert_eq!(
+            vec![
+                (WindowId::new(1, 1), rect(200, 6, 100, 94)),
+                (WindowId::new(1, 2), rect(200, 6, 100, 94)),
+                (WindowId::new(1, 3), rect(200, 6, 100, 94)),
+                (WindowId::new(2, 1), rect(0, 0, 100, 100)),
+                (WindowId::new(2, 2), rect(100, 0, 100, 100)),
+            ],
+            mgr.layout_sorted(space, screen),
+        );
+    }
+
+    #[test]
+    fn new_window_placement_is_configurable() {
+        use LayoutCommand::*;
+        use LayoutEvent::*;
+        let mut mgr = LayoutManager::new();
+        let space = SpaceId::new(1);
+        let pid = 1;
+        let w = |idx| WindowId::new(pid, idx);
+
+        // Start with a vertical split in the middle of three windows.
+        let screen = rect(0, 0, 300, 120);
+        _ = mgr.handle_event(SpaceExposed(space, screen.size));
+        _ = mgr.handle_event(WindowsOnScreenUpdated(space, pid, make_windows(pid, 3)));
+        _ = mgr.handle_event(WindowFocused(vec![space], w(2)));
+        _ = mgr.handle_command(Some(space), &[space], Split(Orientation::Vertical));
+        _ = mgr.handle_event(WindowAdded(space, w(4), win_info(), None));
+        assert_eq!(
+            vec![
+                (w(1), rect(0, 0, 100, 120)),
+                (w(2), rect(100, 0, 100, 60)),
+                (w(3), rect(200, 0, 100, 120)),
+                (w(4), rect(100, 60, 100, 60)),
+            ],
+            mgr.layout_sorted(space, screen),
+        );
+
+        let mut add_window = |placement, weight, under_cursor| {
+            let mut config = Config::default();
+            config.settings.new_window_placement = placement;
+            config.settings.new_window_weight = weight;
+            mgr.set_config(&config);
+            _ = mgr.handle_event(WindowFocused(vec![space], w(2)));
+            _ = mgr.handle_event(WindowAdded(space, w(5), win_info(), under_cursor));
+            let layout = mgr.layout_sorted(space, screen);
+            _ = mgr.handle_event(WindowRemoved(w(5)));
+            layout
+        };
+        use NewWindowWeight::*;
+        use TreePlacement::*;
+
+        assert_eq!(
+            vec![
+                (w(1), rect(0, 0, 100, 120)),
+                (w(2), rect(100, 40, 100, 40)),
+                (w(3), rect(200, 0, 100, 120)),
+                (w(4), rect(100, 80, 100, 40)),
+                (w(5), rect(100, 0, 100, 40)),
+            ],
+            add_window(BeforeFocused, Equal, None),
+        );
+        assert_eq!(
+            vec![
+                (w(1), rect(0, 0, 100, 120)),
+                (w(2), rect(100, 0, 100, 40)),
+                (w(3), rect(200, 0, 100, 120)),
+                (w(4), rect(100, 40, 100, 40)),
+                (w(5), rect(100, 80, 100, 40)),
+            ],
+            add_window(EndOfParent, Equal, None),
+        );
+        let at_root_end = vec![
+            (w(1), rect(0, 0, 75, 120)),
+            (w(2), rect(75, 0, 75, 60)),
+            (w(3), rect(150, 0, 75, 120)),
+            (w(4), rect(75, 60, 75, 60)),
+            (w(5), rect(225, 0, 75, 120)),
+        ];
+        assert_eq!(at_root_end, add_window(RootEnd, Equal, None));
+        assert_eq!(at_root_end, add_window(UnderCursor, Equal, Some(w(3))));
+        // The first and last windows are the same size, so the first is used.
+        assert_eq!(
+            vec![
+                (w(1), rect(0, 0, 75, 120)),
+                (w(2), rect(150, 0, 75, 60)),
+                (w(3), rect(225, 0, 75, 120)),
+                (w(4), rect(150, 60, 75, 60)),
+                (w(5), rect(75, 0, 75, 120)),
+            ],
+            add_window(LargestTile, Equal, None),
+        );
+        // Without a window under the cursor, the new window goes after the
+        // focused one. Here it takes half of the focused window's space.
+        assert_eq!(
+            vec![
+                (w(1), rect(0, 0, 100, 120)),
+                (w(2), rect(100, 0, 100, 30)),
+                (w(3), rect(200, 0, 100, 120)),
+                (w(4), rect(100, 60, 100, 60)),
+                (w(5), rect(100, 30, 100, 30)),
+            ],
+            add_window(UnderCursor, Half, None),
+        );
+    }
+
+    #[test]
+    fn presel_places_the_next_window() {
+        use LayoutCommand::*;
+        use LayoutEvent::*;
+        let mut mgr = LayoutManager::new();
+        let space = SpaceId::new(1);
+        let pid = 1;
+
+        let screen = rect(0, 0, 300, 100);
+        _ = mgr.handle_event(SpaceExposed(space, screen.size));
+        _ = mgr.handle_event(WindowsOnScreenUpdated(space, pid, make_windows(pid, 2)));
+        _ = mgr.handle_event(WindowFocused(vec![space], WindowId::new(pid, 1)));
+        let presel = PreselArg::WithRatio {
+            direction: Direction::Up,
+            ratio: 0.25,
+        };
+        _ = mgr.handle_command(Some(space), &[space], Presel(presel));
+        let layout = mgr.layout(space);
+        let node = mgr.tree.window_node(layout, WindowId::new(pid, 1)).unwrap();
+        assert_eq!(
+            Some(&super::Presel {
+                layout,
+                node,
+                direction: Direction::Up,
+                ratio: 0.25,
+            }),
+            mgr.presel(space),
+        );
+
+        _ = mgr.handle_event(WindowAdded(space, WindowId::new(pid, 3), win_info(), None));
+        assert_eq!(None, mgr.presel(space));
+        assert_eq!(
+            vec![
+                (WindowId::new(pid, 1), rect(0, 25, 150, 75)),
+                (WindowId::new(pid, 2), rect(150, 0, 150, 100)),
+                (WindowId::new(pid, 3), rect(0, 0, 150, 25)),
+            ],
+            mgr.layout_sorted(space, screen),
+        );
+
+        // The presel is only used once.
+        _ = mgr.handle_event(WindowAdded(space, WindowId::new(pid, 4), win_info(), None));
+        _ = mgr.handle_event(WindowRemoved(WindowId::new(pid, 4)));
+
+        // Cancelling goes back to adding windows after the selection.
+        _ = mgr.handle_event(WindowFocused(vec![space], WindowId::new(pid, 2)));
+        _ = mgr.handle_command(
+            Some(space),
+            &[space],
+            Presel(PreselArg::Direction(Direction::Left)),
+        );
+        assert!(mgr.presel(space).is_some());
+        _ = mgr.handle_command(
+            Some(space),
+            &[space],
+            Presel(PreselArg::Cancel(PreselCancel::Cancel)),
+        );
+        assert_eq!(None, mgr.presel(space));
+        _ = mgr.handle_event(WindowAdded(space, WindowId::new(pid, 5), win_info(), None));
+        assert_eq!(
+            vec![
+                (WindowId::new(pid, 1), rect(0, 25, 100, 75)),
+                (WindowId::new(pid, 2), rect(100, 0, 100, 100)),
+                (WindowId::new(pid, 3), rect(0, 0, 100, 25)),
+                (WindowId::new(pid, 5), rect(200, 0, 100, 100)),
+            ],
+            mgr.layout_sorted(space, screen),
+        );
+    }
+
+    #[test]
+    fn add_remove_add() {
+        use LayoutEvent::*;
+        let mut mgr = LayoutManager::new();
+        let space = SpaceId::new(1);
+        let pid = 1;
 
         let screen1 = rect(0, 0, 300, 30);
         _ = mgr.handle_event(SpaceExposed(space, screen1.size));
         _ = mgr.handle_event(WindowsOnScreenUpdated(space, pid, vec![]));
-        _ = mgr.handle_event(WindowAdded(space, WindowId::new(pid, 1), win_info()));
-        _ = mgr.handle_event(WindowAdded(space, WindowId::new(pid, 2), win_info()));
-        _ = mgr.handle_event(WindowAdded(space, WindowId::new(pid, 3), win_info()));
+        _ = mgr.handle_event(WindowAdded(space, WindowId::new(pid, 1), win_info(), None));
+        _ = mgr.handle_event(WindowAdded(space, WindowId::new(pid, 2), win_info(), None));
+        _ = mgr.handle_event(WindowAdded(space, WindowId::new(pid, 3), win_info(), None));
 
         assert_eq!(
             vec![
@@ -1887,9 +2821,9 @@ mod tests {
         _ = mgr.handle_event(WindowRemoved(WindowId::new(pid, 3)));
         _ = mgr.handle_event(WindowRemoved(WindowId::new(pid, 1)));
         _ = mgr.handle_event(WindowRemoved(WindowId::new(pid, 2)));
-        _ = mgr.handle_event(WindowAdded(space, WindowId::new(pid, 1), win_info()));
-        _ = mgr.handle_event(WindowAdded(space, WindowId::new(pid, 2), win_info()));
-        _ = mgr.handle_event(WindowAdded(space, WindowId::new(pid, 3), win_info()));
+        _ = mgr.handle_event(WindowAdded(space, WindowId::new(pid, 1), win_info(), None));
+        _ = mgr.handle_event(WindowAdded(space, WindowId::new(pid, 2), win_info(), None));
+        _ = mgr.handle_event(WindowAdded(space, WindowId::new(pid, 3), win_info(), None));
 
         assert_eq!(
             vec![
@@ -1911,9 +2845,9 @@ mod tests {
         let screen1 = rect(0, 0, 300, 30);
         _ = mgr.handle_event(SpaceExposed(space, screen1.size));
         _ = mgr.handle_event(WindowsOnScreenUpdated(space, pid, vec![]));
-        _ = mgr.handle_event(WindowAdded(space, WindowId::new(pid, 1), win_info()));
-        _ = mgr.handle_event(WindowAdded(space, WindowId::new(pid, 2), win_info()));
-        _ = mgr.handle_event(WindowAdded(space, WindowId::new(pid, 3), win_info()));
+        _ = mgr.handle_event(WindowAdded(space, WindowId::new(pid, 1), win_info(), None));
+        _ = mgr.handle_event(WindowAdded(space, WindowId::new(pid, 2), win_info(), None));
+        _ = mgr.handle_event(WindowAdded(space, WindowId::new(pid, 3), win_info(), None));
 
         assert_eq!(
             vec![
@@ -1971,9 +2905,9 @@ mod tests {
         let screen1_full = rect(0, 0, 300, 30);
         _ = mgr.handle_event(SpaceExposed(space, screen1.size));
         _ = mgr.handle_event(WindowsOnScreenUpdated(space, pid, vec![]));
-        _ = mgr.handle_event(WindowAdded(space, WindowId::new(pid, 1), win_info()));
-        _ = mgr.handle_event(WindowAdded(space, WindowId::new(pid, 2), win_info()));
-        _ = mgr.handle_event(WindowAdded(space, WindowId::new(pid, 3), win_info()));
+        _ = mgr.handle_event(WindowAdded(space, WindowId::new(pid, 1), win_info(), None));
+        _ = mgr.handle_event(WindowAdded(space, WindowId::new(pid, 2), win_info(), None));
+        _ = mgr.handle_event(WindowAdded(space, WindowId::new(pid, 3), win_info(), None));
 
         let orig = vec![
             (WindowId::new(pid, 1), rect(0, 10, 100, 20)),
@@ -2003,6 +2937,88 @@ mod tests {
         assert_eq!(orig, mgr.layout_sorted(space, screen1));
     }
 
+    #[test]
+    fn wrapping_at_the_edge_of_a_layout() {
+        use LayoutCommand::*;
+        use LayoutEvent::*;
+        let mut mgr = LayoutManager::new();
+        let space1 = SpaceId::new(1);
+        let space2 = SpaceId::new(2);
+        let spaces = [space1, space2];
+        let pid = 1;
+        let w = |idx| WindowId::new(pid, idx);
+
+        let screen1 = rect(0, 0, 300, 30);
+        let screen2 = rect(300, 0, 300, 30);
+        _ = mgr.handle_event(SpaceExposed(space1, screen1.size));
+        _ = mgr.handle_event(SpaceExposed(space2, screen2.size));
+        _ = mgr.handle_event(WindowsOnScreenUpdated(space1, pid, make_windows(pid, 3)));
+        _ = mgr.handle_event(WindowsOnScreenUpdated(space2, pid, vec![(w(4), win_info())]));
+        _ = mgr.handle_event(WindowFocused(spaces.to_vec(), w(4)));
+        _ = mgr.handle_event(WindowFocused(spaces.to_vec(), w(3)));
+        let set_wrapping = |mgr: &mut LayoutManager, wrapping| {
+            let mut config = Config::default();
+            config.settings.focus_wrapping = wrapping;
+            config.settings.move_node_wrapping = wrapping;
+            mgr.set_config(&config);
+        };
+        let unchanged = vec![
+            (w(1), rect(0, 0, 100, 30)),
+            (w(2), rect(100, 0, 100, 30)),
+            (w(3), rect(200, 0, 100, 30)),
+        ];
+
+        set_wrapping(&mut mgr, WrapMode::None);
+        let response = mgr.handle_command(Some(space1), &spaces, MoveFocus(Direction::Right));
+        assert_eq!(None, response.focus_window);
+        _ = mgr.handle_command(Some(space1), &spaces, MoveNode(Direction::Right));
+        assert_eq!(unchanged, mgr.layout_sorted(space1, screen1));
+        assert_eq!(vec![(w(4), screen2)], mgr.layout_sorted(space2, screen2));
+
+        set_wrapping(&mut mgr, WrapMode::Workspace);
+        let response = mgr.handle_command(Some(space1), &spaces, MoveFocus(Direction::Right));
+        assert_eq!(Some(w(1)), response.focus_window);
+        let response = mgr.handle_command(Some(space1), &spaces, MoveFocus(Direction::Left));
+        assert_eq!(Some(w(3)), response.focus_window);
+        _ = mgr.handle_command(Some(space1), &spaces, MoveNode(Direction::Right));
+        assert_eq!(
+            vec![
+                (w(1), rect(100, 0, 100, 30)),
+                (w(2), rect(200, 0, 100, 30)),
+                (w(3), rect(0, 0, 100, 30)),
+            ],
+            mgr.layout_sorted(space1, screen1),
+        );
+        _ = mgr.handle_command(Some(space1), &spaces, MoveNode(Direction::Left));
+        assert_eq!(unchanged, mgr.layout_sorted(space1, screen1));
+        assert_eq!(vec![(w(4), screen2)], mgr.layout_sorted(space2, screen2));
+
+        set_wrapping(&mut mgr, WrapMode::Screen);
+        let response = mgr.handle_command(Some(space1), &spaces, MoveFocus(Direction::Right));
+        assert_eq!(Some(w(4)), response.focus_window);
+
+        // The setting only applies to tree layouts; scroll layouts always
+        // continue on the next screen.
+        let mut config = config_with_scroll(true, LayoutKind::Tree);
+        config.settings.focus_wrapping = WrapMode::None;
+        config.settings.move_node_wrapping = WrapMode::None;
+        mgr.set_config(&config);
+        _ = mgr.handle_command(Some(space1), &spaces, ChangeLayoutKind);
+        assert!(mgr.is_scroll_layout(space1));
+        let response = mgr.handle_command(Some(space1), &spaces, MoveFocus(Direction::Right));
+        assert_eq!(Some(w(4)), response.focus_window);
+        // The first move only takes the window out of its column.
+        _ = mgr.handle_command(Some(space1), &spaces, MoveNode(Direction::Right));
+        _ = mgr.handle_command(Some(space1), &spaces, MoveNode(Direction::Right));
+        assert_eq!(
+            vec![w(3), w(4)],
+            mgr.layout_sorted(space2, screen2)
+                .into_iter()
+                .map(|(wid, _)| wid)
+                .collect::<Vec<_>>(),
+        );
+    }
+
     #[test]
     fn flip_between_screens() {
         use LayoutCommand::*;
@@ -2109,6 +3125,7 @@ mod tests {
             Resize {
                 direction: Direction::Right,
                 percent: 10.0,
+                px: None,
             },
         );
         assert_eq!(
@@ -2126,6 +3143,7 @@ mod tests {
             Resize {
                 direction: Direction::Left,
                 percent: 10.0,
+                px: None,
             },
         );
         assert_eq!(
@@ -2137,6 +3155,260 @@ mod tests {
         );
     }
 
+    #[test]
+    fn cycle_focus_through_windows_and_tabs() {
+        use LayoutCommand::*;
+        use LayoutEvent::*;
+        let mut mgr = LayoutManager::new();
+        let space = SpaceId::new(1);
+        let pid = 1;
+        let w = |idx| WindowId::new(pid, idx);
+
+        // Build 1 [2 4 5] 3, where the middle is a tabbed group.
+        _ = mgr.handle_event(SpaceExposed(space, rect(0, 0, 300, 30).size));
+        _ = mgr.handle_event(WindowsOnScreenUpdated(space, pid, make_windows(pid, 3)));
+        _ = mgr.handle_event(WindowFocused(vec![space], w(2)));
+        _ = mgr.handle_command(Some(space), &[space], Split(Orientation::Horizontal));
+        _ = mgr.handle_command(Some(space), &[space], Group(Orientation::Horizontal));
+        _ = mgr.handle_event(WindowAdded(space, w(4), win_info(), None));
+        _ = mgr.handle_event(WindowFocused(vec![space], w(4)));
+        _ = mgr.handle_event(WindowAdded(space, w(5), win_info(), None));
+        _ = mgr.handle_event(WindowFocused(vec![space], w(1)));
+
+        let mut run = |command| {
+            let response = mgr.handle_command(Some(space), &[space], command);
+            if let Some(wid) = response.focus_window {
+                _ = mgr.handle_event(WindowFocused(vec![space], wid));
+            }
+            response.focus_window
+        };
+
+        assert_eq!(Some(w(2)), run(FocusNext));
+        assert_eq!(Some(w(4)), run(FocusNext));
+        assert_eq!(Some(w(5)), run(FocusNext));
+        assert_eq!(Some(w(3)), run(FocusNext));
+        assert_eq!(Some(w(1)), run(FocusNext));
+        assert_eq!(Some(w(3)), run(FocusPrev));
+        assert_eq!(Some(w(5)), run(FocusPrev));
+
+        assert_eq!(Some(w(2)), run(NextTab));
+        assert_eq!(Some(w(5)), run(PrevTab));
+        assert_eq!(Some(w(4)), run(FocusTab(2)));
+        assert_eq!(None, run(FocusTab(2)));
+        assert_eq!(None, run(FocusTab(4)));
+        assert_eq!(None, run(FocusTab(0)));
+
+        // Outside of a group, the tab commands do nothing.
+        assert_eq!(Some(w(5)), run(FocusNext));
+        assert_eq!(Some(w(3)), run(FocusNext));
+        assert_eq!(None, run(NextTab));
+    }
+
+    #[test]
+    fn swap_exchanges_windows_and_keeps_sizes() {
+        use LayoutCommand::*;
+        use LayoutEvent::*;
+        let mut mgr = LayoutManager::new();
+        let space = SpaceId::new(1);
+        let pid = 1;
+        let windows = make_windows(pid, 3);
+
+        let screen = rect(0, 0, 300, 100);
+        _ = mgr.handle_event(SpaceExposed(space, screen.size));
+        _ = mgr.handle_event(WindowsOnScreenUpdated(space, pid, windows));
+        _ = mgr.handle_event(WindowFocused(vec![space], WindowId::new(pid, 2)));
+        _ = mgr.handle_command(Some(space), &[space], Split(Orientation::Vertical));
+        _ = mgr.handle_event(WindowAdded(space, WindowId::new(pid, 4), win_info(), None));
+        _ = mgr.handle_event(WindowFocused(vec![space], WindowId::new(pid, 1)));
+        _ = mgr.handle_command(
+            Some(space),
+            &[space],
+            Resize {
+                direction: Direction::Right,
+                percent: 10.0,
+                px: None,
+            },
+        );
+        let orig = vec![
+            (WindowId::new(pid, 1), rect(0, 0, 130, 100)),
+            (WindowId::new(pid, 2), rect(130, 0, 70, 50)),
+            (WindowId::new(pid, 3), rect(200, 0, 100, 100)),
+            (WindowId::new(pid, 4), rect(130, 50, 70, 50)),
+        ];
+        assert_eq!(orig, mgr.layout_sorted(space, screen));
+
+        let response = mgr.handle_command(Some(space), &[space], Swap(Direction::Right));
+        assert_eq!(Some(WindowId::new(pid, 1)), response.focus_window);
+        assert_eq!(Some(WindowId::new(pid, 1)), mgr.selected_window(space));
+        assert_eq!(
+            vec![
+                (WindowId::new(pid, 1), rect(130, 0, 70, 50)),
+                (WindowId::new(pid, 2), rect(0, 0, 130, 100)),
+                (WindowId::new(pid, 3), rect(200, 0, 100, 100)),
+                (WindowId::new(pid, 4), rect(130, 50, 70, 50)),
+            ],
+            mgr.layout_sorted(space, screen),
+        );
+
+        _ = mgr.handle_command(Some(space), &[space], Swap(Direction::Down));
+        assert_eq!(Some(WindowId::new(pid, 1)), mgr.selected_window(space));
+        assert_eq!(
+            vec![
+                (WindowId::new(pid, 1), rect(130, 50, 70, 50)),
+                (WindowId::new(pid, 2), rect(0, 0, 130, 100)),
+                (WindowId::new(pid, 3), rect(200, 0, 100, 100)),
+                (WindowId::new(pid, 4), rect(130, 0, 70, 50)),
+            ],
+            mgr.layout_sorted(space, screen),
+        );
+
+        // Nothing to swap with.
+        let before = mgr.layout_sorted(space, screen);
+        _ = mgr.handle_command(Some(space), &[space], Swap(Direction::Down));
+        assert_eq!(before, mgr.layout_sorted(space, screen));
+
+        _ = mgr.handle_command(Some(space), &[space], SwapWithMaster);
+        assert_eq!(Some(WindowId::new(pid, 1)), mgr.selected_window(space));
+        assert_eq!(
+            vec![
+                (WindowId::new(pid, 1), rect(0, 0, 130, 100)),
+                (WindowId::new(pid, 2), rect(130, 50, 70, 50)),
+                (WindowId::new(pid, 3), rect(200, 0, 100, 100)),
+                (WindowId::new(pid, 4), rect(130, 0, 70, 50)),
+            ],
+            mgr.layout_sorted(space, screen),
+        );
+
+        // Swapping the master with itself does nothing.
+        let before = mgr.layout_sorted(space, screen);
+        _ = mgr.handle_command(Some(space), &[space], SwapWithMaster);
+        assert_eq!(before, mgr.layout_sorted(space, screen));
+    }
+
+    #[test]
+    fn tree_transforms_apply_to_selected_container() {
+        use LayoutCommand::*;
+        use LayoutEvent::*;
+        let mut mgr = LayoutManager::new();
+        let space = SpaceId::new(1);
+        let pid = 1;
+        let windows = make_windows(pid, 3);
+
+        let screen = rect(0, 0, 300, 300);
+        _ = mgr.handle_event(SpaceExposed(space, screen.size));
+        _ = mgr.handle_event(WindowsOnScreenUpdated(space, pid, windows));
+        _ = mgr.handle_event(WindowFocused(vec![space], WindowId::new(pid, 1)));
+        _ = mgr.handle_command(
+            Some(space),
+            &[space],
+            Resize {
+                direction: Direction::Right,
+                percent: 10.0,
+                px: None,
+            },
+        );
+        assert_eq!(
+            vec![
+                (WindowId::new(pid, 1), rect(0, 0, 130, 300)),
+                (WindowId::new(pid, 2), rect(130, 0, 70, 300)),
+                (WindowId::new(pid, 3), rect(200, 0, 100, 300)),
+            ],
+            mgr.layout_sorted(space, screen),
+        );
+
+        _ = mgr.handle_command(Some(space), &[space], Balance);
+        assert_eq!(
+            vec![
+                (WindowId::new(pid, 1), rect(0, 0, 100, 300)),
+                (WindowId::new(pid, 2), rect(100, 0, 100, 300)),
+                (WindowId::new(pid, 3), rect(200, 0, 100, 300)),
+            ],
+            mgr.layout_sorted(space, screen),
+        );
+
+        _ = mgr.handle_command(Some(space), &[space], Rotate);
+        assert_eq!(
+            vec![
+                (WindowId::new(pid, 1), rect(0, 0, 300, 100)),
+                (WindowId::new(pid, 2), rect(0, 100, 300, 100)),
+                (WindowId::new(pid, 3), rect(0, 200, 300, 100)),
+            ],
+            mgr.layout_sorted(space, screen),
+        );
+
+        _ = mgr.handle_command(Some(space), &[space], Mirror(Orientation::Vertical));
+        assert_eq!(Some(WindowId::new(pid, 1)), mgr.selected_window(space));
+        assert_eq!(
+            vec![
+                (WindowId::new(pid, 1), rect(0, 200, 300, 100)),
+                (WindowId::new(pid, 2), rect(0, 100, 300, 100)),
+                (WindowId::new(pid, 3), rect(0, 0, 300, 100)),
+            ],
+            mgr.layout_sorted(space, screen),
+        );
+    }
+
+    #[test]
+    fn accordions_follow_the_focus() {
+        use LayoutCommand::*;
+        use LayoutEvent::*;
+        let mut mgr = LayoutManager::new();
+        let space = SpaceId::new(1);
+        let pid = 1;
+        let windows = make_windows(pid, 3);
+
+        let screen = rect(0, 0, 300, 300);
+        _ = mgr.handle_event(SpaceExposed(space, screen.size));
+        _ = mgr.handle_event(WindowsOnScreenUpdated(space, pid, windows));
+        _ = mgr.handle_event(WindowFocused(vec![space], WindowId::new(pid, 1)));
+        _ = mgr.handle_command(Some(space), &[space], Accordion(Orientation::Horizontal));
+        assert_eq!(
+            vec![
+                (WindowId::new(pid, 1), rect(0, 0, 240, 300)),
+                (WindowId::new(pid, 2), rect(240, 0, 30, 300)),
+                (WindowId::new(pid, 3), rect(270, 0, 30, 300)),
+            ],
+            mgr.layout_sorted(space, screen),
+        );
+
+        // The mouse can focus a window by its visible strip.
+        let response = mgr.handle_event(MouseMovedOverWindow {
+            over: (space, WindowId::new(pid, 3)),
+            current_main: Some((space, WindowId::new(pid, 1))),
+        });
+        assert_eq!(Some(WindowId::new(pid, 3)), response.focus_window);
+        _ = mgr.handle_event(WindowFocused(vec![space], WindowId::new(pid, 3)));
+        assert_eq!(
+            vec![
+                (WindowId::new(pid, 1), rect(0, 0, 30, 300)),
+                (WindowId::new(pid, 2), rect(30, 0, 30, 300)),
+                (WindowId::new(pid, 3), rect(60, 0, 240, 300)),
+            ],
+            mgr.layout_sorted(space, screen),
+        );
+
+        let response = mgr.handle_command(Some(space), &[space], MoveFocus(Direction::Left));
+        assert_eq!(Some(WindowId::new(pid, 2)), response.focus_window);
+        assert_eq!(
+            vec![
+                (WindowId::new(pid, 1), rect(0, 0, 30, 300)),
+                (WindowId::new(pid, 2), rect(30, 0, 240, 300)),
+                (WindowId::new(pid, 3), rect(270, 0, 30, 300)),
+            ],
+            mgr.layout_sorted(space, screen),
+        );
+
+        _ = mgr.handle_command(Some(space), &[space], Ungroup);
+        assert_eq!(
+            vec![
+                (WindowId::new(pid, 1), rect(0, 0, 100, 300)),
+                (WindowId::new(pid, 2), rect(100, 0, 100, 300)),
+                (WindowId::new(pid, 3), rect(200, 0, 100, 300)),
+            ],
+            mgr.layout_sorted(space, screen),
+        );
+    }
+
     #[test]
     fn space_exposed_forces_tree_when_scroll_gate_disabled() {
         use LayoutEvent::*;
@@ -2263,4 +3535,177 @@ mod tests {
         assert_eq!(mgr.active_layout_kind(space), LayoutKind::Tree);
         assert_eq!(mgr.layout_sorted(space, screen), before);
     }
+
+    #[test]
+    fn tree_borders_can_be_dragged_to_resize() {
+        use LayoutEvent::*;
+        let mut mgr = LayoutManager::new();
+        let config = Config::default();
+        let space = SpaceId::new(1);
+        let screen = rect(0, 0, 1000, 1000);
+        _ = mgr.handle_event(SpaceExposed(space, screen.size));
+        _ = mgr.handle_event(WindowsOnScreenUpdated(space, 1, make_windows(1, 2)));
+
+        // Edges on the border of the screen can't be dragged.
+        let left = CGPoint::new(2.0, 500.0);
+        assert!(mgr.hit_test_edges(space, left, screen, &config).is_none());
+        let top = CGPoint::new(250.0, 2.0);
+        assert!(mgr.hit_test_edges(space, top, screen, &config).is_none());
+
+        let border = CGPoint::new(498.0, 500.0);
+        let (col, win, edges) = mgr.hit_test_edges(space, border, screen, &config).unwrap();
+        assert!(mgr.begin_interactive_resize(space, col, win, edges, border));
+        assert!(mgr.update_interactive_resize(CGPoint::new(598.0, 500.0), screen));
+        mgr.end_interactive_resize(space, screen, &config);
+        assert_eq!(
+            vec![
+                (WindowId::new(1, 1), rect(0, 0, 600, 1000)),
+                (WindowId::new(1, 2), rect(600, 0, 400, 1000)),
+            ],
+            mgr.layout_sorted(space, screen),
+        );
+
+        // Dragging a left edge to the left grows the window.
+        let layout = mgr.layout(space);
+        let node = mgr.tree.window_node(layout, WindowId::new(1, 2)).unwrap();
+        let edges = ResizeEdge(ResizeEdge::LEFT);
+        let border = CGPoint::new(600.0, 500.0);
+        assert!(mgr.begin_interactive_resize(space, node, node, edges, border));
+        assert!(mgr.update_interactive_resize(CGPoint::new(300.0, 500.0), screen));
+        mgr.end_interactive_resize(space, screen, &config);
+        assert_eq!(
+            vec![
+                (WindowId::new(1, 1), rect(0, 0, 300, 1000)),
+                (WindowId::new(1, 2), rect(300, 0, 700, 1000)),
+            ],
+            mgr.layout_sorted(space, screen),
+        );
+    }
+
+    #[test]
+    fn tree_windows_swap_when_dragged_with_modifiers() {
+        use LayoutEvent::*;
+        let mut mgr = LayoutManager::new();
+        let config = Config::default();
+        let space = SpaceId::new(1);
+        let screen = rect(0, 0, 1000, 1000);
+        _ = mgr.handle_event(SpaceExposed(space, screen.size));
+        _ = mgr.handle_event(WindowsOnScreenUpdated(space, 1, make_windows(1, 2)));
+
+        let start = CGPoint::new(250.0, 500.0);
+        let none = ModifierKeys::default();
+        assert!(mgr.hit_test_window(space, start, screen, none, &config).is_none());
+
+        let alt = ModifierKeys {
+            alt: true,
+            ..Default::default()
+        };
+        let (wid, node) = mgr.hit_test_window(space, start, screen, alt, &config).unwrap();
+        assert_eq!(wid, WindowId::new(1, 1));
+        assert!(mgr.begin_interactive_move(space, wid, node, start));
+        assert!(!mgr.update_interactive_move(CGPoint::new(255.0, 500.0), screen, &config));
+        assert!(mgr.update_interactive_move(CGPoint::new(750.0, 500.0), screen, &config));
+        // Moving within the new position doesn't swap back.
+        assert!(!mgr.update_interactive_move(CGPoint::new(760.0, 500.0), screen, &config));
+        mgr.end_interactive_move(space, screen, &config);
+        assert_eq!(
+            vec![
+                (WindowId::new(1, 1), rect(500, 0, 500, 1000)),
+                (WindowId::new(1, 2), rect(0, 0, 500, 1000)),
+            ],
+            mgr.layout_sorted(space, screen),
+        );
+    }
+
+    #[test]
+    fn dragging_in_a_tree_only_changes_the_layout_for_the_current_screen_size() {
+        use LayoutEvent::*;
+        let mut mgr = LayoutManager::new();
+        let config = Config::default();
+        let space = SpaceId::new(1);
+        let windows = make_windows(1, 2);
+        let screen1 = rect(0, 0, 1000, 1000);
+        _ = mgr.handle_event(SpaceExposed(space, screen1.size));
+        _ = mgr.handle_event(WindowsOnScreenUpdated(space, 1, windows.clone()));
+        let border = CGPoint::new(498.0, 500.0);
+        let (col, win, edges) = mgr.hit_test_edges(space, border, screen1, &config).unwrap();
+        assert!(mgr.begin_interactive_resize(space, col, win, edges, border));
+        assert!(mgr.update_interactive_resize(CGPoint::new(598.0, 500.0), screen1));
+        mgr.end_interactive_resize(space, screen1, &config);
+
+        // The second size starts out sharing the layout of the first.
+        let screen2 = rect(0, 0, 2000, 1000);
+        _ = mgr.handle_event(SpaceExposed(space, screen2.size));
+        _ = mgr.handle_event(WindowsOnScreenUpdated(space, 1, windows.clone()));
+        let border = CGPoint::new(1198.0, 500.0);
+        let (col, win, edges) = mgr.hit_test_edges(space, border, screen2, &config).unwrap();
+        assert!(mgr.begin_interactive_resize(space, col, win, edges, border));
+        assert!(mgr.update_interactive_resize(CGPoint::new(998.0, 500.0), screen2));
+        mgr.end_interactive_resize(space, screen2, &config);
+        let alt = ModifierKeys {
+            alt: true,
+            ..Default::default()
+        };
+        let start = CGPoint::new(250.0, 500.0);
+        let (wid, node) = mgr.hit_test_window(space, start, screen2, alt, &config).unwrap();
+        assert!(mgr.begin_interactive_move(space, wid, node, start));
+        assert!(mgr.update_interactive_move(CGPoint::new(1500.0, 500.0), screen2, &config));
+        mgr.end_interactive_move(space, screen2, &config);
+        let dragged = vec![
+            (WindowId::new(1, 1), rect(1000, 0, 1000, 1000)),
+            (WindowId::new(1, 2), rect(0, 0, 1000, 1000)),
+        ];
+        assert_eq!(dragged, mgr.layout_sorted(space, screen2));
+
+        _ = mgr.handle_event(SpaceExposed(space, screen1.size));
+        _ = mgr.handle_event(WindowsOnScreenUpdated(space, 1, windows.clone()));
+        assert_eq!(
+            vec![
+                (WindowId::new(1, 1), rect(0, 0, 600, 1000)),
+                (WindowId::new(1, 2), rect(600, 0, 400, 1000)),
+            ],
+            mgr.layout_sorted(space, screen1),
+        );
+
+        _ = mgr.handle_event(SpaceExposed(space, screen2.size));
+        _ = mgr.handle_event(WindowsOnScreenUpdated(space, 1, windows.clone()));
+        assert_eq!(dragged, mgr.layout_sorted(space, screen2));
+    }
+
+    #[test]
+    fn tree_windows_are_placed_where_they_are_dropped() {
+        use LayoutEvent::*;
+        let mut mgr = LayoutManager::new();
+        let config = Config::default();
+        let space = SpaceId::new(1);
+        let screen = rect(0, 0, 1000, 1000);
+        _ = mgr.handle_event(SpaceExposed(space, screen.size));
+        _ = mgr.handle_event(WindowsOnScreenUpdated(space, 1, make_windows(1, 2)));
+        let orig = mgr.layout_sorted(space, screen);
+
+        // Dropping a window that wasn't dragged does nothing.
+        assert!(mgr.begin_interactive_drop(space, CGPoint::new(250.0, 10.0), screen, &config));
+        mgr.update_interactive_drop(CGPoint::new(750.0, 900.0), screen, &config);
+        assert!(!mgr.end_interactive_drop(space, None));
+        assert_eq!(orig, mgr.layout_sorted(space, screen));
+
+        // Neither does dropping it back onto itself.
+        assert!(mgr.begin_interactive_drop(space, CGPoint::new(250.0, 10.0), screen, &config));
+        mgr.update_interactive_drop(CGPoint::new(250.0, 900.0), screen, &config);
+        assert!(!mgr.end_interactive_drop(space, Some(WindowId::new(1, 1))));
+        assert_eq!(orig, mgr.layout_sorted(space, screen));
+
+        // Dropping on the bottom of another window splits it.
+        assert!(mgr.begin_interactive_drop(space, CGPoint::new(250.0, 10.0), screen, &config));
+        mgr.update_interactive_drop(CGPoint::new(750.0, 900.0), screen, &config);
+        assert!(mgr.end_interactive_drop(space, Some(WindowId::new(1, 1))));
+        assert_eq!(
+            vec![
+                (WindowId::new(1, 1), rect(0, 500, 1000, 500)),
+                (WindowId::new(1, 2), rect(0, 0, 1000, 500)),
+            ],
+            mgr.layout_sorted(space, screen),
+        );
+        assert!(!mgr.has_interactive_state());
+    }
 }
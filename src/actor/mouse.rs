@@ -5,6 +5,7 @@ use std::cell::RefCell;
 use std::mem::replace;
 use std::rc::Rc;
 use std::sync::Arc;
+use std::time::Instant;
 
 use core_foundation::runloop::{CFRunLoop, kCFRunLoopCommonModes};
 use core_graphics::event::{
@@ -16,7 +17,8 @@ use objc2_foundation::{MainThreadMarker, NSInteger};
 use tracing::{debug, error, warn};
 
 use super::reactor::{self, Event};
-use crate::config::Config;
+use crate::config::{Config, ModifierKeys, MouseAction, MouseBinding};
+use crate::model::hover_focus::MouseSpeed;
 use crate::sys::event;
 use crate::sys::geometry::{CGRectExt, ToICrate};
 use crate::sys::screen::CoordinateConverter;
@@ -48,6 +50,15 @@ struct State {
     above_window_level: NSWindowLevel,
     converter: CoordinateConverter,
     screens: Vec<CGRect>,
+    speed: MouseSpeed,
+}
+
+/// What the mouse moved over, as far as focus follows mouse is concerned.
+enum MouseOver {
+    Window(WindowServerId),
+    /// The desktop, a gap between windows, or a window that isn't focused by
+    /// hovering over it.
+    Nothing,
 }
 
 pub type Sender = actor::Sender<Request>;
@@ -73,6 +84,7 @@ impl Mouse {
             CGEventType::LeftMouseUp,
             CGEventType::RightMouseDown,
             CGEventType::RightMouseUp,
+            CGEventType::OtherMouseDown,
             CGEventType::MouseMoved,
             CGEventType::LeftMouseDragged,
             CGEventType::RightMouseDragged,
@@ -165,7 +177,8 @@ impl Mouse {
         match event_type {
             CGEventType::LeftMouseDown => {
                 let loc = event.location();
-                self.events_tx.send(Event::LeftMouseDown(loc.to_icrate()));
+                let modifiers = modifier_keys(event.get_flags());
+                self.events_tx.send(Event::LeftMouseDown(loc.to_icrate(), modifiers));
             }
             CGEventType::LeftMouseUp => {
                 self.events_tx.send(Event::MouseUp);
@@ -178,8 +191,29 @@ impl Mouse {
                 let loc = event.location();
                 #[cfg(false)]
                 tracing::trace!("Mouse moved {loc:?}");
-                if let Some(wsid) = state.track_mouse_move(loc.to_icrate(), mtm) {
-                    self.events_tx.send(Event::MouseMovedOverWindow(wsid));
+                let speed = state.speed.update(loc.to_icrate(), Instant::now());
+                match state.track_mouse_move(loc.to_icrate(), mtm) {
+                    Some(MouseOver::Window(wsid)) => {
+                        self.events_tx.send(Event::MouseMovedOverWindow(wsid, speed));
+                    }
+                    Some(MouseOver::Nothing) => self.events_tx.send(Event::MouseLeftWindows),
+                    None => {}
+                }
+            }
+            CGEventType::RightMouseDown | CGEventType::OtherMouseDown => {
+                let button = event.get_integer_value_field(EventField::MOUSE_EVENT_BUTTON_NUMBER);
+                let action = match button {
+                    1 => MouseAction::RightClick,
+                    2 => MouseAction::MiddleClick,
+                    _ => return,
+                };
+                let modifiers = modifier_keys(event.get_flags());
+                let binding = MouseBinding { modifiers, action };
+                // The tap only listens, so the click still reaches the app.
+                if self.config.borrow().mouse_command(binding).is_some() {
+                    let loc = event.location().to_icrate();
+                    let window = window_server::get_window_at_point(loc, state.converter, mtm);
+                    self.events_tx.send(Event::MouseButtonDown(binding, window));
                 }
             }
             CGEventType::ScrollWheel => {
@@ -191,8 +225,21 @@ impl Mouse {
                     as f64;
 
                 if delta_x != 0.0 || delta_y != 0.0 {
-                    let alt_held = event.get_flags().contains(CGEventFlags::CGEventFlagAlternate);
-                    self.events_tx.send(Event::ScrollWheel { delta_x, delta_y, alt_held });
+                    let modifiers = modifier_keys(event.get_flags());
+                    let is_bound = (self.config.borrow().mouse.iter())
+                        .any(|(b, _)| b.modifiers == modifiers && b.action.is_scroll());
+                    let window = if is_bound {
+                        let loc = event.location().to_icrate();
+                        window_server::get_window_at_point(loc, state.converter, mtm)
+                    } else {
+                        None
+                    };
+                    self.events_tx.send(Event::ScrollWheel {
+                        delta_x,
+                        delta_y,
+                        modifiers,
+                        window,
+                    });
                 }
             }
             _ => (),
@@ -200,6 +247,15 @@ impl Mouse {
     }
 }
 
+fn modifier_keys(flags: CGEventFlags) -> ModifierKeys {
+    ModifierKeys {
+        ctrl: flags.contains(CGEventFlags::CGEventFlagControl),
+        alt: flags.contains(CGEventFlags::CGEventFlagAlternate),
+        shift: flags.contains(CGEventFlags::CGEventFlagShift),
+        meta: flags.contains(CGEventFlags::CGEventFlagCommand),
+    }
+}
+
 impl State {
     fn hide_mouse(&mut self) {
         if let Err(e) = event::hide_mouse() {
@@ -217,7 +273,9 @@ impl State {
         }
     }
 
-    fn track_mouse_move(&mut self, loc: CGPoint, mtm: MainThreadMarker) -> Option<WindowServerId> {
+    /// Returns what the mouse moved over, or None if there's nothing new to
+    /// report.
+    fn track_mouse_move(&mut self, loc: CGPoint, mtm: MainThreadMarker) -> Option<MouseOver> {
         // This takes on the order of 200µs, which can be a while for something
         // that may run many times a second on the main thread. For now this
         // isn't a problem, but when we start doing anything with UI we might
@@ -263,10 +321,10 @@ impl State {
 
         // Don't focus windows outside the "normal" range.
         if !(0..NSPopUpMenuWindowLevel).contains(&new_window_level) {
-            return None;
+            return Some(MouseOver::Nothing);
         }
 
-        new_window
+        new_window.map(MouseOver::Window)
     }
 }
 
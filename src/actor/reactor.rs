@@ -7,7 +7,6 @@
 //! what is going on. It shares this with the layout actor, and reacts to layout
 //! changes by sending requests out to the other actors in the system.
 
-mod animation;
 mod main_window;
 mod replay;
 
@@ -16,12 +15,11 @@ mod testing;
 
 use std::collections::BTreeMap;
 use std::sync::Arc;
-use std::time::Duration;
-use std::{mem, thread};
+use std::time::{Duration, Instant};
+use std::{iter, mem, thread};
 
-use animation::Animation;
 use main_window::MainWindowTracker;
-use objc2_core_foundation::CGRect;
+use objc2_core_foundation::{CGPoint, CGRect, CGSize};
 use redact::Secret;
 pub use replay::{Record, replay};
 use serde::{Deserialize, Serialize};
@@ -35,11 +33,17 @@ use crate::actor::layout::{self, LayoutCommand, LayoutEvent, LayoutManager, Layo
 use crate::actor::raise::{self, RaiseRequest};
 use crate::actor::{group_bars, status};
 use crate::collections::{HashMap, HashSet};
-use crate::config::Config;
+use crate::config::{Config, FocusMode, ModifierKeys, MouseAction, MouseBinding, TreePlacement};
 use crate::log::{self, MetricsCommand};
+use crate::model::Direction;
+use crate::model::floating;
+use crate::model::geometric_focus::nearest_in_direction;
+use crate::model::hover_focus::HoverFocus;
+use crate::model::scroll_viewport::ScrollAccumulator;
+use crate::model::window_animation::{FrameStep, WindowAnimations};
 use crate::sys::event::MouseState;
 use crate::sys::executor::Executor;
-use crate::sys::geometry::{CGRectDef, CGRectExt, SameAs, round_to_physical};
+use crate::sys::geometry::{CGPointDef, CGRectDef, CGRectExt, SameAs, round_to_physical};
 use crate::sys::screen::{CoordinateConverter, SpaceId};
 use crate::sys::timer::Timer;
 use crate::sys::window_server::{WindowServerId, WindowServerInfo};
@@ -78,6 +82,11 @@ pub enum Event {
     /// WindowsDiscovered are not ordered with respect to space events.
     SpaceChanged(Vec<Option<SpaceId>>, Vec<WindowServerInfo>),
 
+    /// Every space in the order they are numbered for the user, with `None`
+    /// for spaces whose id couldn't be read. Sent before commands that pick a
+    /// space by its number.
+    SpaceNumbersChanged(Vec<Option<SpaceId>>),
+
     /// All running apps at launch have been registered.
     StartupComplete,
 
@@ -106,14 +115,27 @@ pub enum Event {
     ApplicationGloballyActivated(pid_t),
     ApplicationGloballyDeactivated(pid_t),
     ApplicationMainWindowChanged(pid_t, Option<WindowId>, Quiet),
+    /// The app was hidden, e.g. with Cmd-H.
+    ApplicationHidden(pid_t),
+    ApplicationShown(pid_t),
 
     WindowsDiscovered {
         pid: pid_t,
         new: Vec<(WindowId, WindowInfo)>,
         known_visible: Vec<WindowId>,
     },
-    WindowCreated(WindowId, WindowInfo, Option<WindowServerInfo>, MouseState),
+    /// A window was created. The last field is where the mouse was at the
+    /// time, if it could be read.
+    WindowCreated(
+        WindowId,
+        WindowInfo,
+        Option<WindowServerInfo>,
+        MouseState,
+        #[serde_as(as = "Option<CGPointDef>")] Option<CGPoint>,
+    ),
     WindowDestroyed(WindowId),
+    WindowMinimized(WindowId),
+    WindowDeminimized(WindowId),
     WindowFrameChanged(
         WindowId,
         #[serde(with = "CGRectDef")] CGRect,
@@ -131,9 +153,15 @@ pub enum Event {
     /// FIXME: This can be interleaved incorrectly with the MouseState in app
     /// actor events.
     MouseUp,
-    /// The mouse cursor moved over a new window. Only sent if focus-follows-
-    /// mouse is enabled.
-    MouseMovedOverWindow(WindowServerId),
+    /// The mouse cursor moved over a new window at the given speed, in points
+    /// per second. Only sent if focus-follows-mouse is enabled.
+    MouseMovedOverWindow(WindowServerId, f64),
+    /// The mouse cursor moved off of the windows it can focus, onto the
+    /// desktop for instance. Only sent if focus-follows-mouse is enabled.
+    MouseLeftWindows,
+    /// A mouse button was pressed with modifier keys that are bound to a
+    /// command, over the given window.
+    MouseButtonDown(MouseBinding, Option<WindowServerId>),
 
     /// A raise request completed. Used by the raise manager to track when
     /// all raise requests in a sequence have finished.
@@ -150,6 +178,7 @@ pub enum Event {
 
     LeftMouseDown(
         #[serde(with = "crate::sys::geometry::CGPointDef")] objc2_core_foundation::CGPoint,
+        ModifierKeys,
     ),
     LeftMouseDragged(
         #[serde(with = "crate::sys::geometry::CGPointDef")] objc2_core_foundation::CGPoint,
@@ -158,7 +187,10 @@ pub enum Event {
     ScrollWheel {
         delta_x: f64,
         delta_y: f64,
-        alt_held: bool,
+        modifiers: ModifierKeys,
+        /// The window under the mouse. Only looked up if a mouse binding
+        /// uses the modifier keys.
+        window: Option<WindowServerId>,
     },
 
     Command(Command),
@@ -182,6 +214,30 @@ pub enum ReactorCommand {
     Debug,
     Serialize,
     SaveAndExit,
+    CloseWindow,
+    MinimizeWindow,
+    HideApp,
+    /// Focus the next window of the app with this bundle id.
+    FocusApp(String),
+    /// Move the selection to the space with this number, starting from 1.
+    /// The space must be on one of the screens.
+    MoveNodeToSpace(usize),
+    /// Move the selection to another screen.
+    MoveNodeToScreen(ScreenTarget),
+    /// Focus the space with this number, starting from 1. The space must be
+    /// on one of the screens.
+    FocusSpace(usize),
+    /// Focus another screen.
+    FocusScreen(ScreenTarget),
+}
+
+/// A screen picked by direction from the current one, or by its number in
+/// the order macOS lists screens, starting from 1.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(untagged)]
+pub enum ScreenTarget {
+    Direction(Direction),
+    Index(usize),
 }
 
 use crate::actor::raise::RaiseManager;
@@ -194,17 +250,70 @@ pub struct Reactor {
     window_server_info: HashMap<WindowServerId, WindowServerInfo>,
     window_ids: HashMap<WindowServerId, WindowId>,
     visible_windows: HashSet<WindowServerId>,
+    /// Windows that were on screen when their app was hidden, to put back
+    /// when it is shown.
+    hidden_app_windows: HashMap<pid_t, Vec<WindowId>>,
     screens: Vec<Screen>,
+    /// See [`Event::SpaceNumbersChanged`].
+    space_numbers: Vec<Option<SpaceId>>,
     active_screen_idx: Option<u16>,
     main_window_tracker: MainWindowTracker,
     in_drag: bool,
+    /// The window the user moved during the current drag, if any.
+    dragged_window: Option<WindowId>,
     record: Record,
     raise_manager_tx: raise::Sender,
     mouse_tx: Option<mouse::Sender>,
     status_tx: Option<status::Sender>,
     group_indicators_tx: group_bars::Sender,
+    window_animations: WindowAnimations,
+    /// Set when an event changed the layout. Cleared by [`Reactor::flush_layout`].
+    pending_layout: Option<PendingLayout>,
+    /// The window that was just asked to close or minimize, or whose app was
+    /// asked to hide. Its neighbour is focused if it leaves the layout before
+    /// the deadline and before the next command.
+    focus_neighbor_of: Option<(WindowId, Instant)>,
+    scroll_bindings: ScrollBindingState,
+    /// The window under the mouse while a mouse binding runs. Commands act on
+    /// it instead of the main window.
+    command_target: Option<WindowId>,
+    /// The window under the mouse, waiting to be focused.
+    hover_focus: HoverFocus<WindowId>,
+    /// Apps that would have been launched. Tests don't launch anything.
+    #[cfg(test)]
+    launched_apps: Vec<String>,
+}
+
+/// A layout update waiting for the current batch of events to be handled.
+#[derive(Debug, Default)]
+struct PendingLayout {
+    new_wids: HashSet<WindowId>,
+    skip_anim: bool,
 }
 
+/// How long the reactor may keep handling queued events before it updates the
+/// layout.
+const MAX_BATCH_LATENCY: Duration = Duration::from_millis(10);
+
+/// How long after asking a window to go away we still focus its neighbour
+/// when it leaves. The request may have been cancelled, for example by a
+/// "Save changes?" sheet.
+const FOCUS_NEIGHBOR_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Scroll progress toward the next command bound to each axis.
+#[derive(Debug, Default)]
+struct ScrollBindingState {
+    modifiers: ModifierKeys,
+    vertical: ScrollAccumulator,
+    horizontal: ScrollAccumulator,
+}
+
+/// Distance of fast scrolling, before sensitivity is applied, that runs a
+/// scroll binding once.
+const SCROLL_BINDING_STEP: f64 = 10.0;
+/// Most times a scroll binding runs for a single event.
+const MAX_SCROLL_BINDING_STEPS: u32 = 16;
+
 #[derive(Debug)]
 struct AppState {
     #[allow(unused)]
@@ -235,6 +344,7 @@ struct WindowState {
     frame_monotonic: CGRect,
     is_ax_standard: bool,
     is_resizable: bool,
+    is_minimized: bool,
     last_sent_txid: TransactionId,
     window_server_id: Option<WindowServerId>,
 }
@@ -254,6 +364,7 @@ impl From<WindowInfo> for WindowState {
             frame_monotonic: info.frame,
             is_ax_standard: info.is_standard,
             is_resizable: info.is_resizable,
+            is_minimized: false,
             last_sent_txid: TransactionId::default(),
             window_server_id: info.sys_id,
         }
@@ -293,6 +404,11 @@ impl Reactor {
         record.start(&config, &layout);
         layout.set_config(&config);
         let (raise_manager_tx, _rx) = mpsc::unbounded_channel();
+        let window_animations = WindowAnimations::new(&config.settings.animation);
+        let hover_focus = HoverFocus::new(
+            Duration::from_millis(config.settings.focus_follows_mouse_delay_ms),
+            config.settings.focus_follows_mouse_speed_threshold,
+        );
         Reactor {
             config,
             apps: HashMap::default(),
@@ -301,15 +417,26 @@ impl Reactor {
             window_ids: HashMap::default(),
             window_server_info: HashMap::default(),
             visible_windows: HashSet::default(),
+            hidden_app_windows: HashMap::default(),
             screens: vec![],
+            space_numbers: vec![],
             active_screen_idx: None,
             main_window_tracker: MainWindowTracker::default(),
             in_drag: false,
+            dragged_window: None,
             record,
             raise_manager_tx,
             mouse_tx: None,
             status_tx: None,
             group_indicators_tx: group_indicators_tx,
+            window_animations,
+            pending_layout: None,
+            focus_neighbor_of: None,
+            scroll_bindings: ScrollBindingState::default(),
+            command_target: None,
+            hover_focus,
+            #[cfg(test)]
+            launched_apps: vec![],
         }
     }
 
@@ -328,47 +455,119 @@ impl Reactor {
         // TODO: Accessibility APIs may be too slow for 120Hz; consider screen-capture animation approach.
         let tick_interval = Duration::from_secs_f64(1.0 / 120.0);
         let mut tick_timer = Timer::manual();
+        let mut hover_timer = Timer::manual();
 
         loop {
-            let animating = self.layout.has_active_scroll_animation();
+            let scrolling = self.layout.has_active_scroll_animation();
+            let animating = scrolling || !self.window_animations.is_empty();
+            let hover_deadline = self.hover_focus.deadline();
+            if let Some(deadline) = hover_deadline {
+                hover_timer.set_next_fire(deadline.saturating_duration_since(Instant::now()));
+            }
             tokio::select! {
                 event = events.recv() => {
                     let Some((span, event)) = event else { break };
-                    let _guard = span.enter();
-                    let was_animating = self.layout.has_active_scroll_animation();
-                    self.handle_event(event);
-                    if !was_animating && self.layout.has_active_scroll_animation() {
+                    span.in_scope(|| self.receive_event(event));
+                    // Handle everything else that is queued before updating
+                    // the layout, so a burst of events only results in one
+                    // layout pass. Stop after a while so a flood of events
+                    // can't keep windows from moving.
+                    let deadline = Instant::now() + MAX_BATCH_LATENCY;
+                    while Instant::now() < deadline
+                        && let Ok((span, event)) = events.try_recv()
+                    {
+                        span.in_scope(|| self.receive_event(event));
+                    }
+                    self.flush_layout();
+                    if !animating && self.is_animating() {
                         tick_timer.set_next_fire(Duration::ZERO);
                     }
                 }
                 _ = tick_timer.next(), if animating => {
-                    self.layout.tick_viewports();
-                    self.update_layout(None, true);
-                    if self.layout.has_active_scroll_animation() {
+                    if scrolling {
+                        self.layout.tick_viewports();
+                        self.update_layout(&HashSet::default(), true);
+                    }
+                    self.tick_window_animations(Instant::now());
+                    if self.is_animating() {
                         tick_timer.set_next_fire(tick_interval);
                     }
                 }
+                _ = hover_timer.next(), if hover_deadline.is_some() => {
+                    self.poll_hover_focus(Instant::now());
+                    self.flush_layout();
+                }
+            }
+        }
+    }
+
+    fn is_animating(&self) -> bool {
+        self.layout.has_active_scroll_animation() || !self.window_animations.is_empty()
+    }
+
+    /// Moves each animating window to where it should be at `now`.
+    fn tick_window_animations(&mut self, now: Instant) {
+        for (wid, step) in self.window_animations.tick(now) {
+            let Some(window) = self.windows.get(&wid) else { continue };
+            let Some(app) = self.apps.get(&wid.pid) else { continue };
+            let txid = window.last_sent_txid;
+            match step {
+                FrameStep::Move(pos) => _ = app.handle.send(Request::SetWindowPos(wid, pos, txid)),
+                FrameStep::Resize(frame) => {
+                    _ = app.handle.send(Request::SetWindowFrame(wid, frame, txid));
+                }
+                FrameStep::Finish(frame) => {
+                    _ = app.handle.send(Request::SetWindowFrame(wid, frame, txid));
+                    _ = app.handle.send(Request::EndWindowAnimation(wid));
+                }
             }
         }
     }
 
+    /// Stops animating a window that left the layout.
+    fn cancel_window_animation(&mut self, wid: WindowId) {
+        if self.window_animations.cancel(wid)
+            && let Some(app) = self.apps.get(&wid.pid)
+        {
+            _ = app.handle.send(Request::EndWindowAnimation(wid));
+        }
+    }
+
     fn log_event(&self, event: &Event) {
         match event {
             // Record more noisy events as trace logs instead of debug.
             Event::WindowFrameChanged(..)
             | Event::MouseUp
-            | Event::LeftMouseDown(_)
+            | Event::LeftMouseDown(..)
             | Event::LeftMouseDragged(_) => trace!(?event, "Event"),
             _ => debug!(?event, "Event"),
         }
     }
 
     fn handle_event(&mut self, event: Event) {
+        self.receive_event(event);
+        self.flush_layout();
+    }
+
+    /// Records and handles an event from outside the reactor without updating
+    /// the layout. Call [`Reactor::flush_layout`] afterward.
+    fn receive_event(&mut self, event: Event) {
         self.record.on_event(&event);
+        self.process_event(event);
+    }
+
+    /// Handles an event without recording it or updating the layout.
+    ///
+    /// Events the reactor sends itself go here, since replaying the event
+    /// that caused them already does so again.
+    fn process_event(&mut self, event: Event) {
         self.log_event(&event);
         let mut animation_focus_wid = None;
         let mut is_resize = false;
         let raised_window = self.main_window_tracker.handle_event(&event);
+        if matches!(event, Event::Command(_)) {
+            self.focus_neighbor_of = None;
+        }
         match event {
             Event::ApplicationLaunched {
                 pid,
@@ -395,6 +594,7 @@ impl Reactor {
             }
             Event::ApplicationThreadTerminated(pid) => {
                 self.apps.remove(&pid);
+                self.hidden_app_windows.remove(&pid);
                 self.send_layout_event(LayoutEvent::AppClosed(pid));
             }
             Event::ApplicationActivated(..)
@@ -407,7 +607,7 @@ impl Reactor {
             Event::WindowsDiscovered { pid, new, known_visible } => {
                 self.on_windows_discovered(pid, new, known_visible);
             }
-            Event::WindowCreated(wid, window, ws_info, mouse_state) => {
+            Event::WindowCreated(wid, window, ws_info, mouse_state, mouse_pos) => {
                 // TODO: It's possible for a window to be on multiple spaces
                 // or move spaces. (Add a test)
                 // FIXME: We assume all windows are on the main screen.
@@ -430,7 +630,13 @@ impl Reactor {
                         is_standard: window.is_standard,
                         is_resizable: window.is_resizable,
                     };
-                    self.send_layout_event(LayoutEvent::WindowAdded(space, wid, info));
+                    let under_cursor = self.tiled_window_under_cursor(space, mouse_pos);
+                    self.send_layout_event(LayoutEvent::WindowAdded(
+                        space,
+                        wid,
+                        info,
+                        under_cursor,
+                    ));
                 }
                 if mouse_state == MouseState::Down {
                     self.in_drag = true;
@@ -441,12 +647,58 @@ impl Reactor {
             Event::WindowDestroyed(wid) => {
                 self.layout.cancel_interactive_state();
                 self.in_drag = false;
+                self.focus_neighbor_if_pending(|w| w == wid);
+                self.window_animations.cancel(wid);
                 if self.windows.remove(&wid).is_none() {
                     warn!("Got destroyed event for unknown window {wid:?}");
                 }
                 //animation_focus_wid = self.window_order.last().cloned();
                 self.send_layout_event(LayoutEvent::WindowRemoved(wid));
             }
+            Event::WindowMinimized(wid) => {
+                let Some(window) = self.windows.get_mut(&wid) else {
+                    return;
+                };
+                window.is_minimized = true;
+                self.focus_neighbor_if_pending(|w| w == wid);
+                self.hide_windows(vec![wid]);
+            }
+            Event::WindowDeminimized(wid) => {
+                let Some(window) = self.windows.get_mut(&wid) else {
+                    return;
+                };
+                window.is_minimized = false;
+                self.show_windows(wid.pid, vec![wid]);
+            }
+            Event::ApplicationHidden(pid) => {
+                self.focus_neighbor_if_pending(|w| w.pid == pid);
+                // Windows on other spaces or already minimized are not part of
+                // the current layout, and should stay out of it when the app
+                // is shown again.
+                let wids = self
+                    .windows
+                    .iter()
+                    .filter(|(wid, window)| {
+                        wid.pid == pid
+                            && window
+                                .window_server_id
+                                .is_some_and(|wsid| self.visible_windows.contains(&wsid))
+                    })
+                    .map(|(wid, _)| *wid)
+                    .collect::<Vec<_>>();
+                self.hidden_app_windows.entry(pid).or_default().extend(&wids);
+                self.hide_windows(wids);
+            }
+            Event::ApplicationShown(pid) => {
+                let wids = self
+                    .hidden_app_windows
+                    .remove(&pid)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|wid| self.windows.get(wid).is_some_and(|w| !w.is_minimized))
+                    .collect();
+                self.show_windows(pid, wids);
+            }
             Event::WindowFrameChanged(wid, new_frame, last_seen, requested, mouse_state) => {
                 let window = self.windows.get_mut(&wid).unwrap();
                 if last_seen != window.last_sent_txid {
@@ -473,15 +725,20 @@ impl Reactor {
                     .collect::<Vec<_>>();
                 // This event is ignored if the window is not in the layout.
                 if old_frame.size != new_frame.size {
-                    self.send_layout_event(LayoutEvent::WindowResized {
-                        wid,
-                        old_frame,
-                        new_frame,
-                        screens,
-                    });
+                    // During an interactive resize the layout already follows
+                    // the mouse, so don't apply the change a second time.
+                    if !self.layout.has_interactive_state() {
+                        self.send_layout_event(LayoutEvent::WindowResized {
+                            wid,
+                            old_frame,
+                            new_frame,
+                            screens,
+                        });
+                    }
                     is_resize = true;
                 } else if mouse_state == Some(MouseState::Down) {
                     self.in_drag = true;
+                    self.dragged_window = Some(wid);
                 }
             }
             Event::ScreenParametersChanged {
@@ -513,6 +770,9 @@ impl Reactor {
                 self.group_indicators_tx
                     .send(group_bars::Event::ScreenParametersChanged(spaces, converter));
             }
+            Event::SpaceNumbersChanged(spaces) => {
+                self.space_numbers = spaces;
+            }
             Event::SpaceChanged(spaces, ws_info) => {
                 if spaces.len() != self.screens.len() {
                     warn!(
@@ -543,20 +803,33 @@ impl Reactor {
                 self.update_active_screen();
                 self.update_visible_windows();
             }
-            Event::LeftMouseDown(point) => {
+            Event::LeftMouseDown(point, modifiers) => {
+                // Clicking focuses the window, so don't change it afterward.
+                self.hover_focus.cancel();
                 if let Some(screen) = self.active_screen()
                     && let Some(space) = screen.space
                 {
                     if let Some((col, win, edges)) =
-                        self.layout.hit_test_scroll_edges(space, point, screen.frame, &self.config)
+                        self.layout.hit_test_edges(space, point, screen.frame, &self.config)
                     {
-                        self.layout.begin_interactive_resize(col, win, edges, point);
+                        self.layout.begin_interactive_resize(space, col, win, edges, point);
                         self.in_drag = true;
-                    } else if let Some((wid, node)) =
-                        self.layout.hit_test_scroll_window(space, point, screen.frame, &self.config)
-                    {
+                    } else if let Some((wid, node)) = self.layout.hit_test_window(
+                        space,
+                        point,
+                        screen.frame,
+                        modifiers,
+                        &self.config,
+                    ) {
                         self.layout.begin_interactive_move(space, wid, node, point);
                         self.in_drag = true;
+                    } else {
+                        self.layout.begin_interactive_drop(
+                            space,
+                            point,
+                            screen.frame,
+                            &self.config,
+                        );
                     }
                 }
             }
@@ -564,13 +837,15 @@ impl Reactor {
                 if let Some(&screen) = self.active_screen() {
                     if screen.space.is_some() {
                         if self.layout.update_interactive_resize(point, screen.frame) {
-                            self.update_layout(None, true);
+                            self.update_layout(&HashSet::default(), true);
                         } else if self.layout.update_interactive_move(
                             point,
                             screen.frame,
                             &self.config,
                         ) {
-                            self.update_layout(None, false);
+                            self.update_layout(&HashSet::default(), false);
+                        } else {
+                            self.layout.update_interactive_drop(point, screen.frame, &self.config);
                         }
                     }
                 }
@@ -584,27 +859,21 @@ impl Reactor {
                         }
                     }
                 }
+                if let Some(space) = self.active_screen().and_then(|screen| screen.space) {
+                    self.layout.end_interactive_drop(space, self.dragged_window.take());
+                }
+                self.dragged_window = None;
                 self.in_drag = false;
                 // Now re-check the layout.
             }
-            Event::MouseMovedOverWindow(wsid) => {
-                let Some(&wid) = self.window_ids.get(&wsid) else { return };
-                let Some(window) = self.windows.get(&wid) else { return };
-                let Some(to_space) = self.best_space_for_window(&window.frame_monotonic) else {
-                    // The space is disabled.
-                    return;
-                };
-                let current_main = match (self.main_window_space(), self.main_window()) {
-                    (Some(space), Some(id)) => Some((space, id)),
-                    _ => None,
-                };
-                self.send_layout_event_from_mouse(
-                    LayoutEvent::MouseMovedOverWindow {
-                        over: (to_space, wid),
-                        current_main,
-                    },
-                    true,
-                );
+            Event::MouseMovedOverWindow(wsid, speed) => {
+                self.mouse_moved_over_window(wsid, speed, Instant::now());
+            }
+            Event::MouseLeftWindows => self.hover_focus.cancel(),
+            Event::MouseButtonDown(binding, window) => {
+                if let Some(command) = self.config.mouse_command(binding) {
+                    self.run_mouse_commands(vec![command.clone()], window);
+                }
             }
             Event::RaiseCompleted { window_id, sequence_id } => {
                 let msg = raise::Event::RaiseCompleted { window_id, sequence_id };
@@ -614,12 +883,17 @@ impl Reactor {
                 let msg = raise::Event::RaiseTimeout { sequence_id };
                 _ = self.raise_manager_tx.send((Span::current(), msg));
             }
-            Event::ScrollWheel { delta_x, delta_y, alt_held } => {
-                if !self.config.settings.experimental.scroll.enable {
+            Event::ScrollWheel {
+                delta_x,
+                delta_y,
+                modifiers,
+                window,
+            } => {
+                if self.handle_scroll_bindings(delta_x, delta_y, modifiers, window) {
                     return;
                 }
-                // TODO: Make the modifier key configurable.
-                if !alt_held {
+                let scroll_config = &self.config.settings.experimental.scroll;
+                if !scroll_config.enable || !modifiers.contains(scroll_config.scroll_modifiers) {
                     return;
                 }
                 if let Some(&screen) = self.active_screen() {
@@ -638,10 +912,32 @@ impl Reactor {
             }
             Event::Command(Command::Layout(cmd)) => {
                 info!(?cmd);
-                let visible_spaces =
-                    self.screens.iter().flat_map(|screen| screen.space).collect::<Vec<_>>();
-                let response =
-                    self.layout.handle_command(self.main_window_space(), &visible_spaces, cmd);
+                self.hover_focus.cancel();
+                if matches!(cmd, LayoutCommand::ToggleWindowFloating)
+                    && let Some(wid) = self.command_window()
+                {
+                    // The layout no longer moves the window if it floats.
+                    self.cancel_window_animation(wid);
+                }
+                let response = match cmd {
+                    LayoutCommand::MoveFocus(direction)
+                        if self.config.settings.focus_mode == FocusMode::Geometric =>
+                    {
+                        match self.geometric_focus(direction) {
+                            Some(response) => response,
+                            None => self.send_layout_command(cmd),
+                        }
+                    }
+                    LayoutCommand::Move { .. }
+                    | LayoutCommand::Resize { .. }
+                    | LayoutCommand::Snap(_)
+                        if self.floating_command_window().is_some() =>
+                    {
+                        self.arrange_floating_window(&cmd);
+                        Default::default()
+                    }
+                    cmd => self.send_layout_command(cmd),
+                };
                 self.handle_layout_response(response);
             }
             Event::Command(Command::Metrics(cmd)) => log::handle_command(cmd),
@@ -655,6 +951,34 @@ impl Reactor {
             Event::Command(Command::Reactor(ReactorCommand::Serialize)) => {
                 println!("{}", self.layout.serialize_to_string());
             }
+            Event::Command(Command::Reactor(ReactorCommand::CloseWindow)) => {
+                self.send_main_window_request(Request::CloseWindow);
+            }
+            Event::Command(Command::Reactor(ReactorCommand::MinimizeWindow)) => {
+                self.send_main_window_request(Request::MinimizeWindow);
+            }
+            Event::Command(Command::Reactor(ReactorCommand::HideApp)) => {
+                self.send_main_window_request(|wid| Request::HideApp(wid.pid));
+            }
+            Event::Command(Command::Reactor(ReactorCommand::FocusApp(bundle_id))) => {
+                self.focus_app(&bundle_id);
+            }
+            Event::Command(Command::Reactor(ReactorCommand::MoveNodeToSpace(number))) => {
+                let space = self.visible_space_with_number(number);
+                self.move_node_to_space(space);
+            }
+            Event::Command(Command::Reactor(ReactorCommand::MoveNodeToScreen(target))) => {
+                let space = self.screen_for_target(target).and_then(|screen| screen.space);
+                self.move_node_to_space(space);
+            }
+            Event::Command(Command::Reactor(ReactorCommand::FocusSpace(number))) => {
+                let space = self.visible_space_with_number(number);
+                self.focus_space(space);
+            }
+            Event::Command(Command::Reactor(ReactorCommand::FocusScreen(target))) => {
+                let space = self.screen_for_target(target).and_then(|screen| screen.space);
+                self.focus_space(space);
+            }
             Event::Command(Command::Reactor(ReactorCommand::SaveAndExit)) => {
                 info!("SaveAndExit command received");
                 match self.layout.save(crate::config::restore_file()) {
@@ -667,6 +991,11 @@ impl Reactor {
             }
             Event::ConfigChanged(config) => {
                 self.layout.set_config(&config);
+                self.window_animations.set_config(&config.settings.animation);
+                self.hover_focus.set_config(
+                    Duration::from_millis(config.settings.focus_follows_mouse_delay_ms),
+                    config.settings.focus_follows_mouse_speed_threshold,
+                );
                 self.config = config;
             }
         }
@@ -676,8 +1005,45 @@ impl Reactor {
             self.update_active_screen();
         }
         if !self.in_drag {
-            self.update_layout(animation_focus_wid, is_resize);
+            let pending = self.pending_layout.get_or_insert_default();
+            pending.new_wids.extend(animation_focus_wid);
+            pending.skip_anim |= is_resize;
+        }
+    }
+
+    /// Updates the layout if any events since the last flush changed it.
+    fn flush_layout(&mut self) {
+        if let Some(PendingLayout { new_wids, skip_anim }) = self.pending_layout.take() {
+            self.update_layout(&new_wids, skip_anim);
+        }
+    }
+
+    /// Takes windows that were minimized or hidden out of the layout, keeping
+    /// track of where they were.
+    fn hide_windows(&mut self, wids: Vec<WindowId>) {
+        // These windows are no longer on screen, so make sure they aren't
+        // added back by the next window discovery.
+        for wid in &wids {
+            self.cancel_window_animation(*wid);
+            if let Some(wsid) = self.windows.get(wid).and_then(|w| w.window_server_id) {
+                self.visible_windows.remove(&wsid);
+            }
+        }
+        self.send_layout_event(LayoutEvent::WindowsHidden(wids));
+    }
+
+    /// Puts windows of one app back in the layout where they were before being
+    /// hidden.
+    fn show_windows(&mut self, pid: pid_t, wids: Vec<WindowId>) {
+        for wid in &wids {
+            if let Some(wsid) = self.windows.get(wid).and_then(|w| w.window_server_id) {
+                self.visible_windows.insert(wsid);
+            }
         }
+        self.send_layout_event(LayoutEvent::WindowsShown(wids));
+        // In case a window could not be restored, or is on a different screen
+        // now, make sure the layout is up to date with the visible windows.
+        self.on_windows_discovered(pid, vec![], vec![]);
     }
 
     fn update_complete_window_server_info(&mut self, ws_info: Vec<WindowServerInfo>) {
@@ -789,6 +1155,32 @@ impl Reactor {
             .map(|(idx, _)| idx)
     }
 
+    fn set_window_frame(&mut self, wid: WindowId, frame: CGRect) {
+        let (Some(window), Some(app)) = (self.windows.get_mut(&wid), self.apps.get(&wid.pid))
+        else {
+            return;
+        };
+        let txid = window.next_txid();
+        window.frame_monotonic = frame;
+        _ = app.handle.send(Request::SetWindowFrame(wid, frame, txid));
+    }
+
+    /// Finds the tiled window under the mouse, if new windows are placed
+    /// next to it.
+    fn tiled_window_under_cursor(
+        &self,
+        space: SpaceId,
+        mouse_pos: Option<CGPoint>,
+    ) -> Option<WindowId> {
+        if self.config.settings.new_window_placement != TreePlacement::UnderCursor {
+            return None;
+        }
+        let point = mouse_pos?;
+        let screen = self.screens.iter().find(|screen| screen.space == Some(space))?;
+        let frames = self.layout.calculate_layout(space, screen.frame, &self.config);
+        frames.into_iter().find(|(_, frame)| frame.contains(point)).map(|(wid, _)| wid)
+    }
+
     fn best_space_for_window(&self, frame: &CGRect) -> Option<SpaceId> {
         self.screens[self.best_screen_idx_for_window(frame)?].space
     }
@@ -816,6 +1208,207 @@ impl Reactor {
         true
     }
 
+    fn mouse_moved_over_window(&mut self, wsid: WindowServerId, speed: f64, now: Instant) {
+        let Some(&wid) = self.window_ids.get(&wsid) else {
+            // The mouse left for something we don't manage, like the menu bar.
+            self.hover_focus.cancel();
+            return;
+        };
+        if let Some(wid) = self.hover_focus.entered(wid, speed, now) {
+            self.focus_window_under_mouse(wid);
+        }
+    }
+
+    fn poll_hover_focus(&mut self, now: Instant) {
+        if let Some(wid) = self.hover_focus.poll(now) {
+            self.focus_window_under_mouse(wid);
+        }
+    }
+
+    fn focus_window_under_mouse(&mut self, wid: WindowId) {
+        let Some(window) = self.windows.get(&wid) else { return };
+        let Some(to_space) = self.best_space_for_window(&window.frame_monotonic) else {
+            // The space is disabled.
+            return;
+        };
+        let current_main = match (self.main_window_space(), self.main_window()) {
+            (Some(space), Some(id)) => Some((space, id)),
+            _ => None,
+        };
+        self.send_layout_event_from_mouse(
+            LayoutEvent::MouseMovedOverWindow {
+                over: (to_space, wid),
+                current_main,
+            },
+            true,
+        );
+    }
+
+    fn send_layout_command(&mut self, cmd: LayoutCommand) -> layout::EventResponse {
+        let cmd = self.resize_in_percent(cmd);
+        let visible_spaces =
+            self.screens.iter().flat_map(|screen| screen.space).collect::<Vec<_>>();
+        self.layout.handle_command(self.command_space(), &visible_spaces, cmd)
+    }
+
+    /// Focuses the nearest visible window to the command window in
+    /// `direction`, looking at every screen and including floating windows.
+    ///
+    /// Returns None to leave it to the layout: in scroll layouts, which can
+    /// reach columns scrolled out of view, and when there is no window in
+    /// that direction, so the layout's wrapping settings apply.
+    fn geometric_focus(&mut self, direction: Direction) -> Option<layout::EventResponse> {
+        let main = self.command_window()?;
+        if self.command_space().is_some_and(|space| self.layout.is_scroll_layout(space)) {
+            return None;
+        }
+        let mut frames = vec![];
+        for screen in &self.screens {
+            let Some(space) = screen.space else { continue };
+            let tiled = self.layout.visible_window_frames(space, screen.frame, &self.config);
+            let floating = self.layout.floating_windows_in(space).filter_map(|wid| {
+                self.windows.get(&wid).map(|window| (wid, window.frame_monotonic))
+            });
+            frames.extend(tiled.into_iter().chain(floating).map(|(wid, f)| ((space, wid), f)));
+        }
+        let from = match frames.iter().find(|((_, wid), _)| *wid == main) {
+            Some(&(_, frame)) => frame,
+            None => self.windows.get(&main)?.frame_monotonic,
+        };
+        let candidates = frames.into_iter().filter(|((_, wid), _)| *wid != main);
+        let (space, wid) = nearest_in_direction(from, direction, candidates)?;
+        Some(self.layout.focus_window(space, wid))
+    }
+
+    /// Returns the window that commands act on if it is floating.
+    fn floating_command_window(&self) -> Option<WindowId> {
+        self.command_window().filter(|&wid| self.layout.is_floating_window(wid))
+    }
+
+    /// Moves, resizes or snaps the floating window that commands act on,
+    /// keeping it within the visible frame of its screen.
+    fn arrange_floating_window(&mut self, cmd: &LayoutCommand) {
+        let Some(wid) = self.floating_command_window() else {
+            return;
+        };
+        let Some(window) = self.windows.get(&wid) else { return };
+        let frame = window.frame_monotonic;
+        let Some(idx) = self.best_screen_idx_for_window(&frame) else {
+            return;
+        };
+        let screen = self.screens[idx];
+        let settings = &self.config.settings;
+        let area = screen.frame.inset(settings.outer_gap);
+        let frame = match *cmd {
+            LayoutCommand::Move { direction, px } => {
+                floating::move_frame(frame, direction, px, area)
+            }
+            LayoutCommand::Resize { direction, percent, px } => {
+                let percent = percent.clamp(-100.0, 100.0);
+                let px = px.unwrap_or_else(|| percent / 100.0 * extent(screen.frame, direction));
+                floating::resize_frame(frame, direction, px, area)
+            }
+            LayoutCommand::Snap(snap) => {
+                floating::snap_frame(snap, frame, area, settings.inner_gap, &settings.snap_grid)
+            }
+            _ => return,
+        };
+        let frame = round_to_physical(frame, screen.scale_factor);
+        self.set_window_frame(wid, frame);
+    }
+
+    /// Converts a resize in pixels to a percentage of the screen the command
+    /// applies to, which is what the layout works in.
+    fn resize_in_percent(&self, cmd: LayoutCommand) -> LayoutCommand {
+        let LayoutCommand::Resize { direction, px: Some(px), .. } = cmd else {
+            return cmd;
+        };
+        let screen = self
+            .command_space()
+            .and_then(|space| self.screens.iter().find(|screen| screen.space == Some(space)));
+        let Some(screen) = screen else { return cmd };
+        let percent = px / extent(screen.frame, direction) * 100.0;
+        LayoutCommand::Resize { direction, percent, px: None }
+    }
+
+    /// Runs the commands bound to scrolling with `modifiers` held, if any.
+    /// Returns false if neither scroll axis is bound for these modifiers.
+    fn handle_scroll_bindings(
+        &mut self,
+        delta_x: f64,
+        delta_y: f64,
+        modifiers: ModifierKeys,
+        window: Option<WindowServerId>,
+    ) -> bool {
+        let binding = |action| MouseBinding { modifiers, action };
+        let actions = [
+            (delta_y, MouseAction::ScrollUp, MouseAction::ScrollDown),
+            (delta_x, MouseAction::ScrollLeft, MouseAction::ScrollRight),
+        ];
+        if !actions.iter().any(|&(_, pos, neg)| {
+            self.config.mouse_command(binding(pos)).is_some()
+                || self.config.mouse_command(binding(neg)).is_some()
+        }) {
+            return false;
+        }
+
+        let state = &mut self.scroll_bindings;
+        if state.modifiers != modifiers {
+            *state = ScrollBindingState {
+                modifiers,
+                ..Default::default()
+            };
+        }
+        let sensitivity = self.config.settings.scroll_binding_sensitivity.clamp(0.0, 100.0);
+        let accumulators = [&mut state.vertical, &mut state.horizontal];
+        let mut commands = vec![];
+        for ((delta, positive, negative), accumulator) in actions.into_iter().zip(accumulators) {
+            if delta == 0.0 {
+                continue;
+            }
+            let Some(steps) = accumulator.accumulate_event(delta, sensitivity, SCROLL_BINDING_STEP)
+            else {
+                continue;
+            };
+            let action = if steps > 0 { positive } else { negative };
+            if let Some(command) = self.config.mouse_command(binding(action)) {
+                let repeat = steps.unsigned_abs().min(MAX_SCROLL_BINDING_STEPS);
+                commands.extend(iter::repeat_n(command.clone(), repeat as usize));
+            }
+        }
+        if !commands.is_empty() {
+            self.run_mouse_commands(commands, window);
+        }
+        true
+    }
+
+    /// Runs commands bound to the mouse against the window under it, or the
+    /// main window if there is none.
+    fn run_mouse_commands(&mut self, commands: Vec<Command>, window: Option<WindowServerId>) {
+        let target = window.and_then(|wsid| self.window_ids.get(&wsid).copied());
+        if let Some(wid) = target
+            && let Some(window) = self.windows.get(&wid)
+        {
+            // Select the window so layout commands start from it.
+            if let Some(space) = self.best_space_for_window(&window.frame_monotonic) {
+                self.send_layout_event(LayoutEvent::WindowFocused(vec![space], wid));
+            }
+            self.command_target = Some(wid);
+        }
+        for command in commands {
+            self.process_event(Event::Command(command));
+        }
+        // Until the system reports a new focused window, keep the layout in
+        // sync with the one that is actually focused.
+        if let Some(target) = self.command_target.take()
+            && let Some(main) = self.main_window()
+            && main != target
+            && let Some(space) = self.main_window_space()
+        {
+            self.send_layout_event(LayoutEvent::WindowFocused(vec![space], main));
+        }
+    }
+
     fn send_layout_event(&mut self, event: LayoutEvent) {
         self.send_layout_event_from_mouse(event, false);
     }
@@ -878,6 +1471,122 @@ impl Reactor {
         _ = self.raise_manager_tx.send((Span::current(), msg));
     }
 
+    /// Sends a request concerning the main window to the app that owns it.
+    fn send_main_window_request(&mut self, request: impl FnOnce(WindowId) -> Request) {
+        let Some(wid) = self.command_window() else { return };
+        let Some(app) = self.apps.get(&wid.pid) else { return };
+        if app.handle.send(request(wid)).is_ok() {
+            self.focus_neighbor_of = Some((wid, Instant::now() + FOCUS_NEIGHBOR_TIMEOUT));
+        }
+    }
+
+    /// Moves focus away from a window we asked to go away, just before the
+    /// windows matching `leaving` are removed from the layout.
+    fn focus_neighbor_if_pending(&mut self, leaving: impl Fn(WindowId) -> bool) {
+        let Some((wid, deadline)) = self.focus_neighbor_of.take_if(|(wid, _)| leaving(*wid)) else {
+            return;
+        };
+        if Instant::now() > deadline {
+            return;
+        }
+        let Some(window) = self.windows.get(&wid) else { return };
+        let Some(space) = self.best_space_for_window(&window.frame_monotonic) else {
+            return;
+        };
+        let response = self.layout.focus_neighbor(space, wid, leaving);
+        self.handle_layout_response(response);
+    }
+
+    /// Focuses a window of the app with the given bundle id. Repeating this
+    /// cycles through the app's windows. If the app has no windows on a
+    /// managed space, it is launched instead.
+    fn focus_app(&mut self, bundle_id: &str) {
+        let pids = self
+            .apps
+            .iter()
+            .filter(|(_, app)| app.info.bundle_id.as_deref() == Some(bundle_id))
+            .map(|(&pid, _)| pid)
+            .collect::<HashSet<_>>();
+        let mut windows = self
+            .windows
+            .keys()
+            .copied()
+            .filter(|wid| pids.contains(&wid.pid) && self.layout.is_window_managed(*wid))
+            .collect::<Vec<_>>();
+        windows.sort();
+
+        let next = match self.main_window() {
+            Some(main) if windows.contains(&main) => {
+                let idx = windows.iter().position(|&wid| wid == main).unwrap();
+                windows[(idx + 1) % windows.len()]
+            }
+            _ => {
+                // Switching to the app; start with the window it had focused.
+                let main = pids
+                    .iter()
+                    .flat_map(|&pid| self.main_window_tracker.app_main_window(pid))
+                    .find(|wid| windows.contains(wid));
+                match main.or(windows.first().copied()) {
+                    Some(wid) => wid,
+                    None => return self.launch_app(bundle_id),
+                }
+            }
+        };
+        self.handle_layout_response(layout::EventResponse {
+            raise_windows: vec![],
+            focus_window: Some(next),
+        });
+    }
+
+    fn launch_app(&mut self, bundle_id: &str) {
+        #[cfg(test)]
+        self.launched_apps.push(bundle_id.to_owned());
+        #[cfg(not(test))]
+        launch_app(bundle_id);
+    }
+
+    /// Returns the space with the given user-facing number, as long as it is
+    /// on one of the screens. We can't switch screens to other spaces.
+    fn visible_space_with_number(&self, number: usize) -> Option<SpaceId> {
+        let Some(&Some(space)) = self.space_numbers.get(number.checked_sub(1)?) else {
+            warn!("Space {number} does not exist");
+            return None;
+        };
+        if !self.screens.iter().any(|screen| screen.space == Some(space)) {
+            warn!("Space {number} is not on any screen");
+            return None;
+        }
+        Some(space)
+    }
+
+    fn screen_for_target(&self, target: ScreenTarget) -> Option<&Screen> {
+        match target {
+            ScreenTarget::Index(number) => self.screens.get(number.checked_sub(1)?),
+            ScreenTarget::Direction(direction) => {
+                let current = match self.command_space() {
+                    Some(space) => self.screens.iter().find(|s| s.space == Some(space))?,
+                    None => self.active_screen()?,
+                };
+                let others = self.screens.iter().filter(|&s| !std::ptr::eq(s, current));
+                nearest_in_direction(current.frame, direction, others.map(|s| (s, s.frame)))
+            }
+        }
+    }
+
+    fn move_node_to_space(&mut self, space: Option<SpaceId>) {
+        let (Some(from), Some(to)) = (self.command_space(), space) else {
+            return;
+        };
+        let response = self.layout.move_selection_to_space(from, to);
+        self.handle_layout_response(response);
+    }
+
+    fn focus_space(&mut self, space: Option<SpaceId>) {
+        let Some(space) = space else { return };
+        let response = self.layout.focus_space(space);
+        self.handle_layout_response(response);
+    }
+
     /// The main window of the active app, if any.
     fn main_window(&self) -> Option<WindowId> {
         self.main_window_tracker.main_window()
@@ -888,11 +1597,25 @@ impl Reactor {
         self.best_space_for_window(&self.windows.get(&self.main_window()?)?.frame_monotonic)
     }
 
+    /// The window commands act on.
+    fn command_window(&self) -> Option<WindowId> {
+        self.command_target.or_else(|| self.main_window())
+    }
+
+    fn command_space(&self) -> Option<SpaceId> {
+        self.best_space_for_window(&self.windows.get(&self.command_window()?)?.frame_monotonic)
+    }
+
     #[instrument(skip(self), fields())]
-    pub fn update_layout(&mut self, new_wid: Option<WindowId>, skip_anim: bool) {
+    pub fn update_layout(&mut self, new_wids: &HashSet<WindowId>, skip_anim: bool) {
         let main_window = self.main_window();
         trace!(?main_window);
-        let mut anim = Animation::new();
+        // If the user is doing something with the mouse we don't want to
+        // animate on top of that.
+        let animate = !skip_anim
+            && self.config.settings.animate
+            && !self.layout.has_active_scroll_animation();
+        let now = Instant::now();
         for &screen in &self.screens {
             let Some(space) = screen.space else { continue };
             if !skip_anim {
@@ -919,21 +1642,65 @@ impl Reactor {
                 };
                 let txid = window.next_txid();
                 trace!(?wid, ?current_frame, ?target_frame);
-                let is_new = Some(wid) == new_wid;
-                anim.add_window(&app.handle, wid, current_frame, target_frame, is_new, txid);
                 window.frame_monotonic = target_frame;
+                let bundle_id = app.info.bundle_id.as_deref();
+                if !animate || self.config.settings.animation.is_disabled_for(bundle_id) {
+                    _ = app.handle.send(Request::SetWindowFrame(wid, target_frame, txid));
+                    if self.window_animations.cancel(wid) {
+                        _ = app.handle.send(Request::EndWindowAnimation(wid));
+                    }
+                    continue;
+                }
+                let is_new = new_wids.contains(&wid);
+                let mut from = current_frame;
+                if is_new {
+                    // Resize new windows immediately.
+                    from.size = target_frame.size;
+                }
+                if self.window_animations.animate(wid, from, target_frame, now) {
+                    _ = app.handle.send(Request::BeginWindowAnimation(wid));
+                }
+                if is_new {
+                    _ = app.handle.send(Request::SetWindowFrame(wid, from, txid));
+                }
             }
         }
-        // If the user is doing something with the mouse we don't want to
-        // animate on top of that.
-        if skip_anim || !self.config.settings.animate || self.layout.has_active_scroll_animation() {
-            anim.skip_to_end();
-        } else {
-            anim.run();
-        }
     }
 }
 
+/// Returns the size of `frame` along `direction`.
+fn extent(frame: CGRect, direction: Direction) -> f64 {
+    match direction {
+        Direction::Left | Direction::Right => frame.size.width,
+        Direction::Up | Direction::Down => frame.size.height,
+    }
+}
+
+fn launch_app(#[allow(unused)] bundle_id: &str) {
+    #[cfg(not(feature = "exec_cmd"))]
+    {
+        info!(
+            ?bundle_id,
+            "App has no windows to focus. Launching apps requires the exec_cmd feature."
+        );
+        return;
+    }
+
+    // Spawn so we don't block the reactor.
+    #[allow(unreachable_code)]
+    let bundle_id = bundle_id.to_owned();
+    thread::spawn(move || {
+        match std::process::Command::new("open").args(["-b", &bundle_id]).output() {
+            Ok(output) if !output.status.success() => {
+                error!("Failed to launch {bundle_id:?}: {}", output.status);
+                error!("stderr: {}", String::from_utf8_lossy(&output.stderr));
+            }
+            Ok(_) => {}
+            Err(e) => error!("Failed to launch {bundle_id:?}: {e:?}"),
+        }
+    });
+}
+
 #[cfg(test)]
 pub mod tests {
     use itertools::Itertools;
@@ -944,7 +1711,9 @@ pub mod tests {
     use super::*;
     use crate::actor::app::Request;
     use crate::actor::layout::LayoutManager;
-    use crate::model::Direction;
+    use crate::config::WrapMode;
+    use crate::model::floating::{Snap, SnapPosition};
+    use crate::model::{Direction, LayoutKind};
     use crate::sys::window_server::WindowServerId;
 
     #[test]
@@ -1198,6 +1967,7 @@ pub mod tests {
             make_window(2),
             None,
             MouseState::Up,
+            None,
         ));
         reactor.handle_event(Event::WindowDestroyed(WindowId::new(1, 2)));
     }
@@ -1262,6 +2032,7 @@ pub mod tests {
             make_window(2),
             None,
             MouseState::Up,
+            None,
         ));
         reactor.handle_event(Event::WindowDestroyed(WindowId::new(1, 2)));
     }
@@ -1588,6 +2359,1134 @@ pub mod tests {
         );
     }
 
+    fn make_reactor_with_main_window(
+        apps: &mut Apps,
+        screen: CGRect,
+        main_window: WindowId,
+        windows: usize,
+    ) -> Reactor {
+        let mut reactor = Reactor::new_for_test(LayoutManager::new());
+        reactor.handle_event(Event::ScreenParametersChanged {
+            frames: vec![screen],
+            spaces: vec![Some(SpaceId::new(1))],
+            scale_factors: vec![2.0],
+            converter: CoordinateConverter::default(),
+            windows: vec![],
+        });
+        reactor.handle_events(apps.make_app_with_opts(
+            main_window.pid,
+            make_windows(windows),
+            Some(main_window),
+            true,
+            true,
+        ));
+        reactor.handle_event(Event::StartupComplete);
+        reactor.handle_event(Event::ApplicationGloballyActivated(main_window.pid));
+        apps.simulate_until_quiet(&mut reactor);
+        reactor
+    }
+
+    /// Captures raise requests so tests can check which window the reactor
+    /// focuses.
+    fn capture_focus(reactor: &mut Reactor) -> mpsc::UnboundedReceiver<(Span, raise::Event)> {
+        let (raise_manager_tx, raise_manager_rx) = mpsc::unbounded_channel();
+        reactor.raise_manager_tx = raise_manager_tx;
+        raise_manager_rx
+    }
+
+    /// Returns the window focused by the next raise request, if one was sent.
+    fn next_focused_window(
+        raise_manager_rx: &mut mpsc::UnboundedReceiver<(Span, raise::Event)>,
+    ) -> Option<WindowId> {
+        match raise_manager_rx.try_recv() {
+            Ok((_, raise::Event::RaiseRequest(RaiseRequest { focus_window, .. }))) => {
+                focus_window.map(|(wid, _)| wid)
+            }
+            Ok((_, msg)) => panic!("Unexpected event: {msg:?}"),
+            Err(_) => None,
+        }
+    }
+
+    /// Returns the events that were recorded for replay.
+    fn recorded_events(reactor: &mut Reactor) -> Vec<String> {
+        let temp = reactor.record.temp().unwrap();
+        std::fs::read_to_string(temp.path())
+            .unwrap()
+            .lines()
+            .skip(2)
+            .map(String::from)
+            .collect()
+    }
+
+    #[test]
+    fn close_window_focuses_neighbor_and_fills_its_space() {
+        let mut apps = Apps::new();
+        let space = SpaceId::new(1);
+        let screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(900., 900.));
+        let mut reactor = make_reactor_with_main_window(&mut apps, screen, WindowId::new(1, 2), 3);
+
+        reactor.handle_event(Event::Command(Command::Reactor(ReactorCommand::CloseWindow)));
+        let requests = apps.requests();
+        assert!(
+            matches!(requests[..], [Request::CloseWindow(wid)] if wid == WindowId::new(1, 2)),
+            "{requests:?}"
+        );
+        for event in apps.simulate_events_for_requests(requests) {
+            reactor.handle_event(event);
+        }
+        apps.simulate_until_quiet(&mut reactor);
+
+        assert_eq!(reactor.layout.selected_window(space), Some(WindowId::new(1, 1)));
+        assert_eq!(
+            apps.windows[&WindowId::new(1, 1)].frame,
+            CGRect::new(CGPoint::new(0., 0.), CGSize::new(450., 900.))
+        );
+        assert_eq!(
+            apps.windows[&WindowId::new(1, 3)].frame,
+            CGRect::new(CGPoint::new(450., 0.), CGSize::new(450., 900.))
+        );
+    }
+
+    #[test]
+    fn cancelled_close_does_not_move_focus_later() {
+        let mut apps = Apps::new();
+        let screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(900., 900.));
+        let mut reactor = make_reactor_with_main_window(&mut apps, screen, WindowId::new(1, 2), 3);
+        let mut raise_manager_rx = capture_focus(&mut reactor);
+
+        // The app asks to save changes, so the window stays open. Another
+        // command is run before the window goes away.
+        reactor.handle_event(Event::Command(Command::Reactor(ReactorCommand::CloseWindow)));
+        apps.requests();
+        reactor.handle_event(Event::Command(Command::Layout(LayoutCommand::Resize {
+            direction: Direction::Right,
+            percent: 10.0,
+            px: None,
+        })));
+        apps.simulate_until_quiet(&mut reactor);
+        while raise_manager_rx.try_recv().is_ok() {}
+        reactor.handle_event(Event::WindowDestroyed(WindowId::new(1, 2)));
+        assert_eq!(next_focused_window(&mut raise_manager_rx), None);
+
+        // The same goes for a window that takes too long to go away.
+        reactor.handle_event(Event::Command(Command::Reactor(ReactorCommand::CloseWindow)));
+        apps.requests();
+        let (wid, _) = reactor.focus_neighbor_of.unwrap();
+        reactor.focus_neighbor_of = Some((wid, Instant::now() - Duration::from_millis(1)));
+        reactor.handle_event(Event::WindowDestroyed(wid));
+        assert_eq!(next_focused_window(&mut raise_manager_rx), None);
+    }
+
+    #[test]
+    fn minimize_window_removes_it_from_the_layout() {
+        let mut apps = Apps::new();
+        let space = SpaceId::new(1);
+        let screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(900., 900.));
+        let mut reactor = make_reactor_with_main_window(&mut apps, screen, WindowId::new(1, 1), 3);
+
+        reactor.handle_event(Event::Command(Command::Reactor(ReactorCommand::MinimizeWindow)));
+        apps.simulate_until_quiet(&mut reactor);
+
+        // There is nothing to the left, so focus moves right.
+        assert_eq!(reactor.layout.selected_window(space), Some(WindowId::new(1, 2)));
+        let windows = reactor
+            .layout
+            .calculate_layout(space, screen, &reactor.config)
+            .into_iter()
+            .map(|(wid, _)| wid)
+            .sorted()
+            .collect_vec();
+        assert_eq!(windows, [WindowId::new(1, 2), WindowId::new(1, 3)]);
+
+        // Rediscovering windows does not bring the minimized window back.
+        reactor.handle_event(Event::WindowsDiscovered {
+            pid: 1,
+            new: vec![],
+            known_visible: vec![],
+        });
+        assert_eq!(
+            reactor.layout.calculate_layout(space, screen, &reactor.config).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn hide_app_focuses_a_window_from_another_app() {
+        let mut apps = Apps::new();
+        let space = SpaceId::new(1);
+        let screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(900., 900.));
+        let mut reactor = make_reactor_with_main_window(&mut apps, screen, WindowId::new(1, 1), 2);
+        let mut windows = make_windows(1);
+        windows[0].sys_id = Some(WindowServerId::new(3));
+        reactor.handle_events(apps.make_app(2, windows));
+        apps.simulate_until_quiet(&mut reactor);
+
+        reactor.handle_event(Event::Command(Command::Reactor(ReactorCommand::HideApp)));
+        apps.simulate_until_quiet(&mut reactor);
+
+        // Window 1 of app 1 is skipped over because it is hidden too.
+        assert_eq!(reactor.layout.selected_window(space), Some(WindowId::new(2, 1)));
+        assert_eq!(
+            reactor.layout.calculate_layout(space, screen, &reactor.config),
+            vec![(WindowId::new(2, 1), screen)]
+        );
+        assert_eq!(apps.windows[&WindowId::new(2, 1)].frame, screen);
+    }
+
+    #[test]
+    fn deminimized_window_returns_to_its_place() {
+        let mut apps = Apps::new();
+        let space = SpaceId::new(1);
+        let screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(900., 900.));
+        let mut reactor = make_reactor_with_main_window(&mut apps, screen, WindowId::new(1, 2), 3);
+        reactor.handle_event(Event::Command(Command::Layout(LayoutCommand::Resize {
+            direction: Direction::Right,
+            percent: 10.0,
+            px: None,
+        })));
+        apps.simulate_until_quiet(&mut reactor);
+        let frames = apps.windows.iter().map(|(&wid, w)| (wid, w.frame)).collect_vec();
+
+        reactor.handle_event(Event::Command(Command::Reactor(ReactorCommand::MinimizeWindow)));
+        apps.simulate_until_quiet(&mut reactor);
+        assert_eq!(
+            reactor.layout.calculate_layout(space, screen, &reactor.config).len(),
+            2
+        );
+
+        reactor.handle_event(Event::WindowDeminimized(WindowId::new(1, 2)));
+        apps.simulate_until_quiet(&mut reactor);
+        assert_eq!(
+            apps.windows.iter().map(|(&wid, w)| (wid, w.frame)).collect_vec(),
+            frames
+        );
+    }
+
+    #[test]
+    fn shown_app_leaves_windows_that_were_not_on_screen() {
+        let mut apps = Apps::new();
+        let screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(900., 900.));
+        let mut reactor = make_reactor_with_main_window(&mut apps, screen, WindowId::new(1, 1), 2);
+        // A window on another space, or minimized before we started, is known
+        // but not visible.
+        let mut offscreen = make_window(3);
+        offscreen.sys_id = Some(WindowServerId::new(99));
+        reactor.handle_event(Event::WindowsDiscovered {
+            pid: 1,
+            new: vec![(WindowId::new(1, 3), offscreen)],
+            known_visible: vec![],
+        });
+        apps.simulate_until_quiet(&mut reactor);
+        let tiled = |reactor: &Reactor| {
+            reactor
+                .layout
+                .calculate_layout(SpaceId::new(1), screen, &reactor.config)
+                .into_iter()
+                .map(|(wid, _)| wid)
+                .sorted()
+                .collect_vec()
+        };
+        assert_eq!(tiled(&reactor), [WindowId::new(1, 1), WindowId::new(1, 2)]);
+
+        reactor.handle_event(Event::ApplicationHidden(1));
+        reactor.handle_event(Event::ApplicationShown(1));
+        apps.simulate_until_quiet(&mut reactor);
+        assert_eq!(tiled(&reactor), [WindowId::new(1, 1), WindowId::new(1, 2)]);
+        assert!(!reactor.visible_windows.contains(&WindowServerId::new(99)));
+    }
+
+    #[test]
+    fn shown_app_returns_to_its_place() {
+        let mut apps = Apps::new();
+        let screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(900., 900.));
+        let mut reactor = make_reactor_with_main_window(&mut apps, screen, WindowId::new(1, 1), 2);
+        let mut windows = make_windows(1);
+        windows[0].sys_id = Some(WindowServerId::new(3));
+        reactor.handle_events(apps.make_app(2, windows));
+        apps.simulate_until_quiet(&mut reactor);
+        let frames = apps.windows.iter().map(|(&wid, w)| (wid, w.frame)).collect_vec();
+
+        // Hide an app with a minimized window; showing the app should not
+        // bring back the minimized window.
+        reactor.handle_event(Event::WindowMinimized(WindowId::new(1, 2)));
+        reactor.handle_event(Event::ApplicationHidden(1));
+        apps.simulate_until_quiet(&mut reactor);
+        assert_eq!(apps.windows[&WindowId::new(2, 1)].frame, screen);
+
+        reactor.handle_event(Event::ApplicationShown(1));
+        apps.simulate_until_quiet(&mut reactor);
+        assert_eq!(
+            reactor
+                .layout
+                .calculate_layout(SpaceId::new(1), screen, &reactor.config)
+                .into_iter()
+                .map(|(wid, _)| wid)
+                .sorted()
+                .collect_vec(),
+            [WindowId::new(1, 1), WindowId::new(2, 1)]
+        );
+
+        reactor.handle_event(Event::WindowDeminimized(WindowId::new(1, 2)));
+        apps.simulate_until_quiet(&mut reactor);
+        assert_eq!(
+            apps.windows.iter().map(|(&wid, w)| (wid, w.frame)).collect_vec(),
+            frames
+        );
+    }
+
+    #[test]
+    fn focus_app_cycles_through_its_windows() {
+        let mut apps = Apps::new();
+        let screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(900., 900.));
+        let mut reactor = make_reactor_with_main_window(&mut apps, screen, WindowId::new(1, 1), 1);
+        let mut windows = make_windows(2);
+        windows[0].sys_id = Some(WindowServerId::new(2));
+        windows[1].sys_id = Some(WindowServerId::new(3));
+        reactor.handle_events(apps.make_app(2, windows));
+        apps.simulate_until_quiet(&mut reactor);
+        let mut raise_manager_rx = capture_focus(&mut reactor);
+
+        let mut focus_app = |reactor: &mut Reactor, bundle_id: &str| {
+            reactor.handle_event(Event::Command(Command::Reactor(ReactorCommand::FocusApp(
+                bundle_id.to_owned(),
+            ))));
+            next_focused_window(&mut raise_manager_rx)
+        };
+
+        // Switching to the app focuses its main window first.
+        assert_eq!(
+            focus_app(&mut reactor, "com.testapp2"),
+            Some(WindowId::new(2, 1))
+        );
+        reactor.handle_event(Event::ApplicationActivated(2, Quiet::No));
+        reactor.handle_event(Event::ApplicationGloballyActivated(2));
+        assert_eq!(
+            focus_app(&mut reactor, "com.testapp2"),
+            Some(WindowId::new(2, 2))
+        );
+        reactor.handle_event(Event::ApplicationMainWindowChanged(
+            2,
+            Some(WindowId::new(2, 2)),
+            Quiet::No,
+        ));
+        assert_eq!(
+            focus_app(&mut reactor, "com.testapp2"),
+            Some(WindowId::new(2, 1))
+        );
+
+        assert_eq!(
+            focus_app(&mut reactor, "com.testapp1"),
+            Some(WindowId::new(1, 1))
+        );
+        assert_eq!(focus_app(&mut reactor, "com.example.NotRunning"), None);
+        assert_eq!(reactor.launched_apps, ["com.example.NotRunning"]);
+    }
+
+    fn make_animating_reactor() -> Reactor {
+        let mut reactor = Reactor::new_for_test(LayoutManager::new());
+        let mut config = Config::default();
+        config.settings.default_disable = false;
+        config.settings.animate = true;
+        reactor.config = Arc::new(config);
+        reactor
+    }
+
+    #[test]
+    fn window_animations_are_driven_by_ticks() {
+        let mut reactor = make_animating_reactor();
+        let screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+        reactor.handle_event(Event::ScreenParametersChanged {
+            frames: vec![screen],
+            spaces: vec![Some(SpaceId::new(1))],
+            scale_factors: vec![2.0],
+            converter: CoordinateConverter::default(),
+            windows: vec![],
+        });
+        let mut apps = Apps::new();
+        reactor.handle_events(apps.make_app(1, make_windows(2)));
+        reactor.handle_event(Event::StartupComplete);
+        apps.simulate_until_quiet(&mut reactor);
+
+        // Nothing has moved yet; the windows are waiting for the next tick.
+        let left = CGRect::new(CGPoint::new(0., 0.), CGSize::new(500., 1000.));
+        let right = CGRect::new(CGPoint::new(500., 0.), CGSize::new(500., 1000.));
+        assert!(reactor.is_animating());
+        assert!(apps.windows[&WindowId::new(1, 1)].animating);
+        assert_ne!(apps.windows[&WindowId::new(1, 1)].frame, left);
+
+        let start = Instant::now();
+        reactor.tick_window_animations(start + Duration::from_millis(50));
+        apps.simulate_until_quiet(&mut reactor);
+        let frame = apps.windows[&WindowId::new(1, 2)].frame;
+        assert!(frame.origin.x > 200. && frame.origin.x < 500., "{frame:?}");
+        assert!(apps.windows[&WindowId::new(1, 2)].animating);
+
+        reactor.tick_window_animations(start + Duration::from_secs(5));
+        apps.simulate_until_quiet(&mut reactor);
+        assert!(!reactor.is_animating());
+        assert!(!apps.windows[&WindowId::new(1, 1)].animating);
+        assert_eq!(apps.windows[&WindowId::new(1, 1)].frame, left);
+        assert_eq!(apps.windows[&WindowId::new(1, 2)].frame, right);
+    }
+
+    #[test]
+    fn windows_leaving_the_layout_stop_animating() {
+        let mut reactor = make_animating_reactor();
+        let screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+        reactor.handle_event(Event::ScreenParametersChanged {
+            frames: vec![screen],
+            spaces: vec![Some(SpaceId::new(1))],
+            scale_factors: vec![2.0],
+            converter: CoordinateConverter::default(),
+            windows: vec![],
+        });
+        let mut apps = Apps::new();
+        let main = WindowId::new(1, 2);
+        reactor.handle_events(apps.make_app_with_opts(1, make_windows(4), Some(main), true, true));
+        reactor.handle_event(Event::StartupComplete);
+        reactor.handle_event(Event::ApplicationGloballyActivated(1));
+        apps.simulate_until_quiet(&mut reactor);
+        let animating = |reactor: &Reactor, apps: &Apps, idx| {
+            let wid = WindowId::new(1, idx);
+            assert_eq!(
+                reactor.window_animations.is_animating(wid),
+                apps.windows[&wid].animating
+            );
+            reactor.window_animations.is_animating(wid)
+        };
+        assert!((1..=4).all(|idx| animating(&reactor, &apps, idx)));
+
+        reactor.handle_event(Event::WindowMinimized(WindowId::new(1, 1)));
+        reactor.handle_event(Event::Command(Command::Layout(
+            LayoutCommand::ToggleWindowFloating,
+        )));
+        apps.windows.remove(&WindowId::new(1, 3));
+        reactor.handle_event(Event::WindowDestroyed(WindowId::new(1, 3)));
+        apps.simulate_until_quiet(&mut reactor);
+        assert!(!animating(&reactor, &apps, 1));
+        assert!(!animating(&reactor, &apps, 2));
+        assert!(!reactor.window_animations.is_animating(WindowId::new(1, 3)));
+        assert!(animating(&reactor, &apps, 4));
+
+        // Ticks leave the windows that stopped animating alone.
+        let frame = apps.windows[&WindowId::new(1, 1)].frame;
+        reactor.tick_window_animations(Instant::now() + Duration::from_secs(5));
+        apps.simulate_until_quiet(&mut reactor);
+        assert_eq!(apps.windows[&WindowId::new(1, 1)].frame, frame);
+        assert!(!reactor.is_animating());
+    }
+
+    #[test]
+    fn layout_changes_retarget_animating_windows() {
+        let mut reactor = make_animating_reactor();
+        let screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+        reactor.handle_event(Event::ScreenParametersChanged {
+            frames: vec![screen],
+            spaces: vec![Some(SpaceId::new(1))],
+            scale_factors: vec![2.0],
+            converter: CoordinateConverter::default(),
+            windows: vec![],
+        });
+        let mut apps = Apps::new();
+        reactor.handle_events(apps.make_app(1, make_windows(1)));
+        reactor.handle_event(Event::StartupComplete);
+        apps.simulate_until_quiet(&mut reactor);
+        assert!(reactor.is_animating());
+
+        // A second window shows up before the first finished animating.
+        let mut windows = make_windows(1);
+        windows[0].sys_id = Some(WindowServerId::new(2));
+        reactor.handle_events(apps.make_app(2, windows));
+        apps.simulate_until_quiet(&mut reactor);
+
+        reactor.tick_window_animations(Instant::now() + Duration::from_secs(5));
+        apps.simulate_until_quiet(&mut reactor);
+        assert!(!reactor.is_animating());
+        assert_eq!(
+            apps.windows[&WindowId::new(1, 1)].frame,
+            CGRect::new(CGPoint::new(0., 0.), CGSize::new(500., 1000.))
+        );
+        assert_eq!(
+            apps.windows[&WindowId::new(2, 1)].frame,
+            CGRect::new(CGPoint::new(500., 0.), CGSize::new(500., 1000.))
+        );
+    }
+
+    #[test]
+    fn apps_can_opt_out_of_animations() {
+        let mut reactor = make_animating_reactor();
+        let mut config = Config::default();
+        config.settings.default_disable = false;
+        config.settings.animation.disabled_apps = vec!["com.testapp1".into()];
+        reactor.handle_event(Event::ConfigChanged(Arc::new(config)));
+        let screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+        reactor.handle_event(Event::ScreenParametersChanged {
+            frames: vec![screen],
+            spaces: vec![Some(SpaceId::new(1))],
+            scale_factors: vec![2.0],
+            converter: CoordinateConverter::default(),
+            windows: vec![],
+        });
+        let mut apps = Apps::new();
+        reactor.handle_events(apps.make_app(1, make_windows(1)));
+        let mut windows = make_windows(1);
+        windows[0].sys_id = Some(WindowServerId::new(2));
+        reactor.handle_events(apps.make_app(2, windows));
+        reactor.handle_event(Event::StartupComplete);
+        apps.simulate_until_quiet(&mut reactor);
+
+        assert!(!reactor.window_animations.is_animating(WindowId::new(1, 1)));
+        assert!(reactor.window_animations.is_animating(WindowId::new(2, 1)));
+        assert_eq!(
+            apps.windows[&WindowId::new(1, 1)].frame,
+            CGRect::new(CGPoint::new(0., 0.), CGSize::new(500., 1000.))
+        );
+    }
+
+    #[test]
+    fn bursts_of_events_are_laid_out_once() {
+        let screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+        let new_windows = || {
+            (2..=10)
+                .map(|idx| {
+                    let info = make_window(idx);
+                    let ws_info = WindowServerInfo {
+                        pid: 1,
+                        id: info.sys_id.unwrap(),
+                        layer: 0,
+                        frame: info.frame,
+                    };
+                    Event::WindowCreated(
+                        WindowId::new(1, idx as u32),
+                        info,
+                        Some(ws_info),
+                        MouseState::Up,
+                        None,
+                    )
+                })
+                .collect_vec()
+        };
+        let frame_writes = |apps: &mut Apps| {
+            apps.requests()
+                .into_iter()
+                .filter(|request| matches!(request, Request::SetWindowFrame(..)))
+                .count()
+        };
+
+        let mut one_by_one = (Apps::new(), Reactor::new_for_test(LayoutManager::new()));
+        let mut batched = (Apps::new(), Reactor::new_for_test(LayoutManager::new()));
+        for (apps, reactor) in [&mut one_by_one, &mut batched] {
+            reactor.handle_event(Event::ScreenParametersChanged {
+                frames: vec![screen],
+                spaces: vec![Some(SpaceId::new(1))],
+                scale_factors: vec![2.0],
+                converter: CoordinateConverter::default(),
+                windows: vec![],
+            });
+            reactor.handle_events(apps.make_app(1, make_windows(1)));
+            reactor.handle_event(Event::StartupComplete);
+            apps.simulate_until_quiet(reactor);
+        }
+
+        let (apps, reactor) = &mut one_by_one;
+        reactor.handle_events(new_windows());
+        let one_by_one_writes = frame_writes(apps);
+        let (apps, reactor) = &mut batched;
+        reactor.handle_events_batched(new_windows());
+        let batched_writes = frame_writes(apps);
+
+        // Each window is written once instead of every time a window is added.
+        assert_eq!(batched_writes, 10);
+        assert!(one_by_one_writes > batched_writes, "{one_by_one_writes}");
+
+        let frames = |reactor: &Reactor| {
+            (reactor.windows.iter())
+                .map(|(&wid, window)| (wid, window.frame_monotonic))
+                .sorted_by_key(|&(wid, _)| wid)
+                .collect_vec()
+        };
+        assert_eq!(frames(&one_by_one.1).len(), 10);
+        assert_eq!(frames(&one_by_one.1), frames(&batched.1));
+    }
+
+    #[test]
+    fn every_new_window_in_a_batch_is_resized_right_away() {
+        let mut reactor = make_animating_reactor();
+        let screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+        reactor.handle_event(Event::ScreenParametersChanged {
+            frames: vec![screen],
+            spaces: vec![Some(SpaceId::new(1))],
+            scale_factors: vec![2.0],
+            converter: CoordinateConverter::default(),
+            windows: vec![],
+        });
+        let mut apps = Apps::new();
+        reactor.handle_events(apps.make_app(1, make_windows(1)));
+        reactor.handle_event(Event::StartupComplete);
+        apps.simulate_until_quiet(&mut reactor);
+        reactor.tick_window_animations(Instant::now() + Duration::from_secs(5));
+        apps.simulate_until_quiet(&mut reactor);
+
+        let new_windows = (2..=3)
+            .map(|idx| {
+                let info = make_window(idx);
+                let ws_info = WindowServerInfo {
+                    pid: 1,
+                    id: info.sys_id.unwrap(),
+                    layer: 0,
+                    frame: info.frame,
+                };
+                Event::WindowCreated(
+                    WindowId::new(1, idx as u32),
+                    info,
+                    Some(ws_info),
+                    MouseState::Up,
+                    None,
+                )
+            })
+            .collect_vec();
+        reactor.handle_events_batched(new_windows);
+        apps.simulate_until_quiet(&mut reactor);
+
+        // New windows only slide into place; they already have their size.
+        for idx in 2..=3 {
+            let wid = WindowId::new(1, idx);
+            assert_eq!(
+                apps.windows[&wid].frame.size, reactor.windows[&wid].frame_monotonic.size,
+                "{wid:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn new_windows_can_be_placed_next_to_the_window_under_the_mouse() {
+        let mut apps = Apps::new();
+        let screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(900., 900.));
+        let mut reactor = make_reactor_with_main_window(&mut apps, screen, WindowId::new(1, 3), 3);
+        let mut config = Config::default();
+        config.settings.default_disable = false;
+        config.settings.animate = false;
+        config.settings.new_window_placement = TreePlacement::UnderCursor;
+        reactor.handle_event(Event::ConfigChanged(Arc::new(config)));
+        apps.simulate_until_quiet(&mut reactor);
+
+        let info = make_window(4);
+        let ws_info = WindowServerInfo {
+            pid: 1,
+            id: info.sys_id.unwrap(),
+            layer: 0,
+            frame: info.frame,
+        };
+        reactor.handle_event(Event::WindowCreated(
+            WindowId::new(1, 4),
+            info,
+            Some(ws_info),
+            MouseState::Up,
+            Some(CGPoint::new(100., 500.)),
+        ));
+        apps.simulate_until_quiet(&mut reactor);
+        let left_edges = (1..=4).map(|idx| apps.windows[&WindowId::new(1, idx)].frame.origin.x);
+        assert_eq!(left_edges.collect_vec(), [0., 450., 675., 225.]);
+    }
+
+    #[test]
+    fn dragged_windows_are_placed_where_they_are_dropped() {
+        let mut apps = Apps::new();
+        let mut reactor = Reactor::new_for_test(LayoutManager::new());
+        let screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+        reactor.handle_event(Event::ScreenParametersChanged {
+            frames: vec![screen],
+            spaces: vec![Some(SpaceId::new(1))],
+            scale_factors: vec![2.0],
+            converter: CoordinateConverter::default(),
+            windows: vec![],
+        });
+        reactor.handle_events(apps.make_app(1, make_windows(2)));
+        reactor.handle_event(Event::StartupComplete);
+        apps.simulate_until_quiet(&mut reactor);
+
+        // Drag the left window by its title bar onto the bottom of the right one.
+        let dragged = WindowId::new(1, 1);
+        reactor.handle_event(Event::LeftMouseDown(
+            CGPoint::new(250., 10.),
+            ModifierKeys::default(),
+        ));
+        let window = &apps.windows[&dragged];
+        let mut frame = window.frame;
+        frame.origin = CGPoint::new(500., 890.);
+        reactor.handle_event(Event::WindowFrameChanged(
+            dragged,
+            frame,
+            window.last_seen_txid,
+            Requested(false),
+            Some(MouseState::Down),
+        ));
+        reactor.handle_event(Event::LeftMouseDragged(CGPoint::new(750., 900.)));
+        assert!(apps.requests().is_empty(), "layout should wait for the drop");
+
+        reactor.handle_event(Event::MouseUp);
+        apps.simulate_until_quiet(&mut reactor);
+        assert_eq!(
+            CGRect::new(CGPoint::new(0., 500.), CGSize::new(1000., 500.)),
+            apps.windows[&dragged].frame,
+        );
+        assert_eq!(
+            CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 500.)),
+            apps.windows[&WindowId::new(1, 2)].frame,
+        );
+    }
+
+    #[test]
+    fn scroll_bindings_act_on_the_window_under_the_mouse() {
+        let mut apps = Apps::new();
+        let screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(900., 900.));
+        let mut reactor = make_reactor_with_main_window(&mut apps, screen, WindowId::new(1, 1), 3);
+        let mut config = Config::default();
+        config.settings.default_disable = false;
+        config.settings.animate = false;
+        config.mouse.push((
+            "Alt + ScrollDown".parse().unwrap(),
+            Command::Layout(LayoutCommand::MoveFocus(Direction::Right)),
+        ));
+        reactor.handle_event(Event::ConfigChanged(Arc::new(config)));
+        let mut raise_manager_rx = capture_focus(&mut reactor);
+
+        let mut scroll = |reactor: &mut Reactor, delta_y, modifiers, window| {
+            reactor.handle_event(Event::ScrollWheel {
+                delta_x: 0.0,
+                delta_y,
+                modifiers,
+                window,
+            });
+            next_focused_window(&mut raise_manager_rx)
+        };
+        let alt = ModifierKeys {
+            alt: true,
+            ..Default::default()
+        };
+        let alt_shift = ModifierKeys { shift: true, ..alt };
+
+        // Without a window under the mouse, the focused window is used.
+        assert_eq!(scroll(&mut reactor, -1.0, alt, None), Some(WindowId::new(1, 2)));
+        // Scrolling over a window acts on that window instead.
+        let left = Some(WindowServerId::new(1));
+        assert_eq!(scroll(&mut reactor, -1.0, alt, left), Some(WindowId::new(1, 2)));
+        // Only the bound direction and modifiers do anything.
+        assert_eq!(scroll(&mut reactor, 1.0, alt, left), None);
+        assert_eq!(scroll(&mut reactor, -1.0, alt_shift, left), None);
+        assert_eq!(scroll(&mut reactor, -1.0, ModifierKeys::default(), left), None);
+
+        // Only the scrolling is recorded, since replaying it runs the commands.
+        let recorded = recorded_events(&mut reactor);
+        assert!(recorded.iter().any(|event| event.starts_with("ScrollWheel(")));
+        assert!(!recorded.iter().any(|event| event.starts_with("Command(")));
+    }
+
+    #[test]
+    fn mouse_button_bindings_act_on_the_window_under_the_mouse() {
+        let mut apps = Apps::new();
+        let space = SpaceId::new(1);
+        let screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(900., 900.));
+        let mut reactor = make_reactor_with_main_window(&mut apps, screen, WindowId::new(1, 1), 3);
+        let mut config = Config::default();
+        config.settings.default_disable = false;
+        config.settings.animate = false;
+        let right_click = "Alt + RightClick".parse().unwrap();
+        let middle_click = "Alt + MiddleClick".parse().unwrap();
+        config
+            .mouse
+            .push((right_click, Command::Layout(LayoutCommand::ToggleWindowFloating)));
+        config.mouse.push((middle_click, Command::Reactor(ReactorCommand::CloseWindow)));
+        reactor.handle_event(Event::ConfigChanged(Arc::new(config)));
+        let tiled = |reactor: &Reactor| {
+            (reactor.layout.calculate_layout(space, screen, &reactor.config).into_iter())
+                .map(|(wid, _)| wid)
+                .sorted()
+                .collect_vec()
+        };
+
+        reactor.handle_event(Event::MouseButtonDown(right_click, Some(WindowServerId::new(3))));
+        assert_eq!(tiled(&reactor), [WindowId::new(1, 1), WindowId::new(1, 2)]);
+
+        apps.simulate_until_quiet(&mut reactor);
+        reactor.handle_event(Event::MouseButtonDown(
+            middle_click,
+            Some(WindowServerId::new(2)),
+        ));
+        let requests = apps.requests();
+        assert!(
+            matches!(requests[..], [Request::CloseWindow(wid)] if wid == WindowId::new(1, 2)),
+            "{requests:?}"
+        );
+
+        // Without a window under the mouse, the main window is used.
+        reactor.handle_event(Event::MouseButtonDown(right_click, None));
+        assert_eq!(tiled(&reactor), [WindowId::new(1, 2)]);
+
+        // Only the clicks are recorded, since replaying them runs the commands.
+        let recorded = recorded_events(&mut reactor);
+        assert!(recorded.iter().any(|event| event.starts_with("MouseButtonDown(")));
+        assert!(!recorded.iter().any(|event| event.starts_with("Command(")));
+    }
+
+    #[test]
+    fn focus_follows_mouse_waits_for_the_mouse_to_rest() {
+        let mut apps = Apps::new();
+        let screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(900., 900.));
+        let mut reactor = make_reactor_with_main_window(&mut apps, screen, WindowId::new(1, 1), 3);
+        let mut config = Config::default();
+        config.settings.default_disable = false;
+        config.settings.animate = false;
+        config.settings.focus_follows_mouse_delay_ms = 100;
+        config.settings.focus_follows_mouse_speed_threshold = 200.0;
+        reactor.handle_event(Event::ConfigChanged(Arc::new(config)));
+        let mut raise_manager_rx = capture_focus(&mut reactor);
+
+        let mut focused = |reactor: &mut Reactor| {
+            reactor.flush_layout();
+            next_focused_window(&mut raise_manager_rx)
+        };
+        let t0 = Instant::now();
+        let ms = Duration::from_millis;
+
+        // Moving quickly over a window only focuses it after the delay.
+        reactor.mouse_moved_over_window(WindowServerId::new(2), 1000.0, t0);
+        assert_eq!(focused(&mut reactor), None);
+        reactor.poll_hover_focus(t0 + ms(50));
+        assert_eq!(focused(&mut reactor), None);
+        reactor.poll_hover_focus(t0 + ms(100));
+        assert_eq!(focused(&mut reactor), Some(WindowId::new(1, 2)));
+
+        // Leaving before the delay cancels it.
+        reactor.mouse_moved_over_window(WindowServerId::new(3), 1000.0, t0 + ms(200));
+        reactor.mouse_moved_over_window(WindowServerId::new(99), 1000.0, t0 + ms(250));
+        reactor.poll_hover_focus(t0 + ms(400));
+        assert_eq!(focused(&mut reactor), None);
+        // Including for the desktop or a gap between windows.
+        reactor.mouse_moved_over_window(WindowServerId::new(3), 1000.0, t0 + ms(400));
+        reactor.handle_event(Event::MouseLeftWindows);
+        reactor.poll_hover_focus(t0 + ms(500));
+        assert_eq!(focused(&mut reactor), None);
+
+        // So does a command.
+        reactor.mouse_moved_over_window(WindowServerId::new(3), 1000.0, t0 + ms(500));
+        reactor.handle_event(Event::Command(Command::Layout(LayoutCommand::MoveFocus(
+            Direction::Left,
+        ))));
+        focused(&mut reactor);
+        reactor.poll_hover_focus(t0 + ms(600));
+        assert_eq!(focused(&mut reactor), None);
+
+        // Moving slowly focuses right away.
+        reactor.mouse_moved_over_window(WindowServerId::new(3), 100.0, t0 + ms(700));
+        assert_eq!(focused(&mut reactor), Some(WindowId::new(1, 3)));
+    }
+
+    #[test]
+    fn geometric_focus_reaches_floating_windows() {
+        let mut apps = Apps::new();
+        let screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(900., 900.));
+        let mut reactor = make_reactor_with_main_window(&mut apps, screen, WindowId::new(1, 1), 3);
+        let mut config = Config::default();
+        config.settings.default_disable = false;
+        config.settings.animate = false;
+        reactor.handle_event(Event::ConfigChanged(Arc::new(config)));
+        apps.simulate_until_quiet(&mut reactor);
+        reactor.handle_event(Event::Command(Command::Layout(
+            LayoutCommand::ToggleWindowFloating,
+        )));
+        apps.simulate_until_quiet(&mut reactor);
+        let mut raise_manager_rx = capture_focus(&mut reactor);
+
+        let mut move_focus = |reactor: &mut Reactor, direction| {
+            reactor.handle_event(Event::Command(Command::Layout(LayoutCommand::MoveFocus(
+                direction,
+            ))));
+            reactor.flush_layout();
+            next_focused_window(&mut raise_manager_rx)
+        };
+
+        // The first window floats over the left half of the second one. The
+        // layout can't move focus away from a floating window.
+        assert_eq!(move_focus(&mut reactor, Direction::Right), None);
+
+        let mut config = Config::default();
+        config.settings.default_disable = false;
+        config.settings.animate = false;
+        config.settings.focus_mode = FocusMode::Geometric;
+        reactor.handle_event(Event::ConfigChanged(Arc::new(config)));
+        assert_eq!(
+            move_focus(&mut reactor, Direction::Right),
+            Some(WindowId::new(1, 2))
+        );
+        assert_eq!(move_focus(&mut reactor, Direction::Left), None);
+    }
+
+    #[test]
+    fn geometric_focus_falls_back_to_the_layout() {
+        let mut apps = Apps::new();
+        let screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(900., 900.));
+        let mut reactor = make_reactor_with_main_window(&mut apps, screen, WindowId::new(1, 3), 3);
+        let mut config = Config::default();
+        config.settings.default_disable = false;
+        config.settings.animate = false;
+        config.settings.focus_mode = FocusMode::Geometric;
+        config.settings.focus_wrapping = WrapMode::Workspace;
+        config.mouse.push((
+            "Alt + ScrollDown".parse().unwrap(),
+            Command::Layout(LayoutCommand::MoveFocus(Direction::Right)),
+        ));
+        reactor.handle_event(Event::ConfigChanged(Arc::new(config)));
+        let mut raise_manager_rx = capture_focus(&mut reactor);
+
+        // There is nothing to the right, so focus wraps around the layout.
+        reactor.handle_event(Event::Command(Command::Layout(LayoutCommand::MoveFocus(
+            Direction::Right,
+        ))));
+        assert_eq!(
+            next_focused_window(&mut raise_manager_rx),
+            Some(WindowId::new(1, 1))
+        );
+
+        // Mouse bindings start from the window under the mouse.
+        reactor.handle_event(Event::ScrollWheel {
+            delta_x: 0.0,
+            delta_y: -1.0,
+            modifiers: ModifierKeys {
+                alt: true,
+                ..Default::default()
+            },
+            window: Some(WindowServerId::new(1)),
+        });
+        assert_eq!(
+            next_focused_window(&mut raise_manager_rx),
+            Some(WindowId::new(1, 2))
+        );
+
+        // Scroll layouts reach columns that are scrolled out of view.
+        let mut apps = Apps::new();
+        let mut reactor = Reactor::new_for_test(LayoutManager::new());
+        let mut config = Config::default();
+        config.settings.default_disable = false;
+        config.settings.animate = false;
+        config.settings.focus_mode = FocusMode::Geometric;
+        config.settings.default_layout_kind = LayoutKind::Scroll;
+        config.settings.experimental.scroll.enable = true;
+        config.settings.experimental.scroll.visible_columns = 1;
+        config.settings.experimental.scroll.animation.instant = true;
+        reactor.handle_event(Event::ConfigChanged(Arc::new(config)));
+        reactor.handle_event(Event::ScreenParametersChanged {
+            frames: vec![screen],
+            spaces: vec![Some(SpaceId::new(1))],
+            scale_factors: vec![2.0],
+            converter: CoordinateConverter::default(),
+            windows: vec![],
+        });
+        let main = WindowId::new(1, 1);
+        reactor.handle_events(apps.make_app_with_opts(1, make_windows(2), Some(main), true, true));
+        reactor.handle_event(Event::StartupComplete);
+        reactor.handle_event(Event::ApplicationGloballyActivated(1));
+        apps.simulate_until_quiet(&mut reactor);
+        assert!(reactor.layout.is_scroll_layout(SpaceId::new(1)));
+        let mut raise_manager_rx = capture_focus(&mut reactor);
+        // The second window is in the column to the left.
+        reactor.handle_event(Event::Command(Command::Layout(LayoutCommand::MoveFocus(
+            Direction::Left,
+        ))));
+        assert_eq!(
+            next_focused_window(&mut raise_manager_rx),
+            Some(WindowId::new(1, 2))
+        );
+    }
+
+    #[test]
+    fn move_node_and_focus_between_screens() {
+        let mut apps = Apps::new();
+        let mut reactor = Reactor::new_for_test(LayoutManager::new());
+        let mut config = Config::default();
+        config.settings.default_disable = false;
+        config.settings.animate = false;
+        reactor.handle_event(Event::ConfigChanged(Arc::new(config)));
+        let (space1, space2) = (SpaceId::new(1), SpaceId::new(2));
+        let screen1 = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+        let screen2 = CGRect::new(CGPoint::new(1000., 0.), CGSize::new(1000., 1000.));
+        reactor.handle_event(Event::ScreenParametersChanged {
+            frames: vec![screen1, screen2],
+            spaces: vec![Some(space1), Some(space2)],
+            scale_factors: vec![2.0, 2.0],
+            converter: CoordinateConverter::default(),
+            windows: vec![],
+        });
+        reactor.handle_events(apps.make_app_with_opts(
+            1,
+            make_windows(2),
+            Some(WindowId::new(1, 1)),
+            true,
+            true,
+        ));
+        reactor.handle_event(Event::StartupComplete);
+        reactor.handle_event(Event::ApplicationGloballyActivated(1));
+        apps.simulate_until_quiet(&mut reactor);
+        let tiled = |reactor: &Reactor, space, screen| {
+            (reactor.layout.calculate_layout(space, screen, &reactor.config).into_iter())
+                .map(|(wid, _)| wid)
+                .sorted()
+                .collect_vec()
+        };
+        assert_eq!(
+            tiled(&reactor, space1, screen1),
+            [WindowId::new(1, 1), WindowId::new(1, 2)]
+        );
+
+        let command = |target| Event::Command(Command::Reactor(target));
+        // There is no screen to the left.
+        reactor.handle_event(command(ReactorCommand::MoveNodeToScreen(
+            ScreenTarget::Direction(Direction::Left),
+        )));
+        assert_eq!(tiled(&reactor, space2, screen2), []);
+
+        reactor.handle_event(command(ReactorCommand::MoveNodeToScreen(
+            ScreenTarget::Direction(Direction::Right),
+        )));
+        apps.simulate_until_quiet(&mut reactor);
+        assert_eq!(tiled(&reactor, space1, screen1), [WindowId::new(1, 2)]);
+        assert_eq!(tiled(&reactor, space2, screen2), [WindowId::new(1, 1)]);
+        assert_eq!(screen2, apps.windows[&WindowId::new(1, 1)].frame);
+
+        let mut raise_manager_rx = capture_focus(&mut reactor);
+        let mut focused = |reactor: &mut Reactor, command| {
+            reactor.handle_event(Event::Command(Command::Reactor(command)));
+            next_focused_window(&mut raise_manager_rx)
+        };
+        assert_eq!(
+            focused(&mut reactor, ReactorCommand::FocusScreen(ScreenTarget::Index(1))),
+            Some(WindowId::new(1, 2))
+        );
+        assert_eq!(
+            focused(&mut reactor, ReactorCommand::FocusScreen(ScreenTarget::Index(3))),
+            None
+        );
+    }
+
+    #[test]
+    fn move_node_and_focus_by_space_number() {
+        let mut apps = Apps::new();
+        let mut reactor = Reactor::new_for_test(LayoutManager::new());
+        let mut config = Config::default();
+        config.settings.default_disable = false;
+        config.settings.animate = false;
+        reactor.handle_event(Event::ConfigChanged(Arc::new(config)));
+        let (space1, space2) = (SpaceId::new(1), SpaceId::new(2));
+        let screen1 = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+        let screen2 = CGRect::new(CGPoint::new(1000., 0.), CGSize::new(1000., 1000.));
+        reactor.handle_event(Event::ScreenParametersChanged {
+            frames: vec![screen1, screen2],
+            spaces: vec![Some(space1), Some(space2)],
+            scale_factors: vec![2.0, 2.0],
+            converter: CoordinateConverter::default(),
+            windows: vec![],
+        });
+        reactor.handle_events(apps.make_app_with_opts(
+            1,
+            make_windows(2),
+            Some(WindowId::new(1, 1)),
+            true,
+            true,
+        ));
+        reactor.handle_event(Event::StartupComplete);
+        reactor.handle_event(Event::ApplicationGloballyActivated(1));
+        apps.simulate_until_quiet(&mut reactor);
+        // Space 2 isn't on a screen and space 3 couldn't be read.
+        reactor.handle_event(Event::SpaceNumbersChanged(vec![
+            Some(space1),
+            Some(SpaceId::new(3)),
+            None,
+            Some(space2),
+        ]));
+        let tiled = |reactor: &Reactor, space, screen| {
+            (reactor.layout.calculate_layout(space, screen, &reactor.config).into_iter())
+                .map(|(wid, _)| wid)
+                .sorted()
+                .collect_vec()
+        };
+
+        let command = |command| Event::Command(Command::Reactor(command));
+        for number in [0, 2, 3, 5] {
+            reactor.handle_event(command(ReactorCommand::MoveNodeToSpace(number)));
+        }
+        assert_eq!(tiled(&reactor, space2, screen2), []);
+
+        reactor.handle_event(command(ReactorCommand::MoveNodeToSpace(4)));
+        apps.simulate_until_quiet(&mut reactor);
+        assert_eq!(tiled(&reactor, space1, screen1), [WindowId::new(1, 2)]);
+        assert_eq!(tiled(&reactor, space2, screen2), [WindowId::new(1, 1)]);
+
+        let mut raise_manager_rx = capture_focus(&mut reactor);
+        let mut focused = |reactor: &mut Reactor, number| {
+            reactor.handle_event(command(ReactorCommand::FocusSpace(number)));
+            next_focused_window(&mut raise_manager_rx)
+        };
+        assert_eq!(focused(&mut reactor, 1), Some(WindowId::new(1, 2)));
+        assert_eq!(focused(&mut reactor, 2), None);
+        assert_eq!(focused(&mut reactor, 4), Some(WindowId::new(1, 1)));
+    }
+
+    #[test]
+    fn move_resize_and_snap_floating_windows() {
+        let mut apps = Apps::new();
+        let screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+        let main = WindowId::new(1, 1);
+        let mut reactor = make_reactor_with_main_window(&mut apps, screen, main, 2);
+        let mut config = Config::default();
+        config.settings.default_disable = false;
+        config.settings.animate = false;
+        config.settings.outer_gap = 10.0;
+        config.settings.inner_gap = 10.0;
+        reactor.handle_event(Event::ConfigChanged(Arc::new(config)));
+        apps.simulate_until_quiet(&mut reactor);
+
+        let mut command = |command| {
+            reactor.handle_event(Event::Command(Command::Layout(command)));
+            apps.simulate_until_quiet(&mut reactor);
+            apps.windows[&main].frame
+        };
+        command(LayoutCommand::ToggleWindowFloating);
+        assert_eq!(
+            CGRect::new(CGPoint::new(505., 10.), CGSize::new(485., 980.)),
+            command(LayoutCommand::Snap(Snap::Position(SnapPosition::RightHalf))),
+        );
+        // Windows stop at the gap around the edge of the screen.
+        assert_eq!(
+            CGRect::new(CGPoint::new(10., 10.), CGSize::new(485., 980.)),
+            command(LayoutCommand::Move {
+                direction: Direction::Left,
+                px: 600.
+            }),
+        );
+        assert_eq!(
+            CGRect::new(CGPoint::new(10., 10.), CGSize::new(485., 500.)),
+            command(LayoutCommand::Resize {
+                direction: Direction::Down,
+                percent: 5.,
+                px: Some(-480.),
+            }),
+        );
+        // Without pixels, resizes are a percentage of the screen.
+        assert_eq!(
+            CGRect::new(CGPoint::new(10., 10.), CGSize::new(535., 500.)),
+            command(LayoutCommand::Resize {
+                direction: Direction::Right,
+                percent: 5.,
+                px: None,
+            }),
+        );
+        // The tiled window is left alone.
+        assert_eq!(
+            CGRect::new(CGPoint::new(10., 10.), CGSize::new(980., 980.)),
+            apps.windows[&WindowId::new(1, 2)].frame,
+        );
+    }
+
     #[test]
     fn no_scroll_animation_when_idle() {
         let mut reactor = Reactor::new_for_test(LayoutManager::new());
@@ -77,19 +77,26 @@ impl MainWindowTracker {
                 (pid, quiet)
             }
             Event::ApplicationTerminated(..)
+            | Event::ApplicationHidden(..)
+            | Event::ApplicationShown(..)
             | Event::StartupComplete
             | Event::WindowsDiscovered { .. }
             | Event::WindowCreated(..)
             | Event::WindowDestroyed(..)
+            | Event::WindowMinimized(..)
+            | Event::WindowDeminimized(..)
             | Event::WindowFrameChanged(..)
             | Event::ScreenParametersChanged { .. }
             | Event::SpaceChanged(..)
+            | Event::SpaceNumbersChanged(..)
             | Event::MouseUp
             | Event::MouseMovedOverWindow(..)
+            | Event::MouseLeftWindows
+            | Event::MouseButtonDown(..)
             | Event::RaiseCompleted { .. }
             | Event::RaiseTimeout { .. }
             | Event::ScrollWheel { .. }
-            | Event::LeftMouseDown(_)
+            | Event::LeftMouseDown(..)
             | Event::LeftMouseDragged(_)
             | Event::Command(..)
             | Event::ConfigChanged(_) => return None,
@@ -102,6 +109,11 @@ impl MainWindowTracker {
         None
     }
 
+    /// The last known main window of an app, whether or not it is active.
+    pub fn app_main_window(&self, pid: pid_t) -> Option<WindowId> {
+        self.apps.get(&pid)?.main_window
+    }
+
     /// The main window of the active app, if any.
     pub fn main_window(&self) -> Option<WindowId> {
         // Because apps self-report this event from their respective
@@ -34,6 +34,15 @@ impl Reactor {
             self.handle_event(event);
         }
     }
+
+    /// Handles the events as one batch, like the reactor does when they are
+    /// already queued.
+    pub fn handle_events_batched(&mut self, events: Vec<Event>) {
+        for event in events {
+            self.receive_event(event);
+        }
+        self.flush_layout();
+    }
 }
 
 impl Drop for Reactor {
@@ -239,6 +248,12 @@ impl Apps {
                         None,
                     ));
                 }
+                Request::CloseWindow(wid) => {
+                    self.windows.remove(&wid);
+                    events.push(Event::WindowDestroyed(wid));
+                }
+                Request::MinimizeWindow(wid) => events.push(Event::WindowMinimized(wid)),
+                Request::HideApp(pid) => events.push(Event::ApplicationHidden(pid)),
                 Request::Raise(..) => todo!(),
                 Request::WindowDestroyed(..) => todo!(),
             }
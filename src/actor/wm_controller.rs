@@ -30,7 +30,7 @@ use crate::actor::{self, group_bars, mouse, reactor, status, window_server};
 use crate::collections::HashSet;
 use crate::sys;
 use crate::sys::event::HotkeyManager;
-use crate::sys::screen::{CoordinateConverter, NSScreenExt, ScreenId, SpaceId};
+use crate::sys::screen::{self, CoordinateConverter, NSScreenExt, ScreenId, SpaceId};
 use crate::sys::window_server::WindowServerInfo;
 
 #[derive(Debug)]
@@ -295,6 +295,15 @@ impl WmController {
                 self.exec_cmd(cmd);
             }
             Command(ReactorCommand(cmd)) => {
+                use reactor::ReactorCommand::{FocusSpace, MoveNodeToSpace};
+                if matches!(
+                    cmd,
+                    reactor::Command::Reactor(MoveNodeToSpace(_) | FocusSpace(_))
+                ) {
+                    // Spaces can be added or reordered without any event, so
+                    // read their numbers right before they are needed.
+                    self.send_event(Event::SpaceNumbersChanged(screen::managed_spaces()));
+                }
                 self.send_event(Event::Command(cmd));
             }
             ConfigUpdated(config) => {
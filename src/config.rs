@@ -21,6 +21,7 @@ use partial::{PartialConfig, ValidationError};
 use rustc_hash::FxHashMap;
 use serde::{Deserialize, Serialize};
 
+use crate::actor::reactor;
 use crate::actor::wm_controller::WmCommand;
 use crate::model::LayoutKind;
 
@@ -58,6 +59,7 @@ fn default_config_paths() -> Vec<PathBuf> {
 pub struct Config {
     pub settings: Settings,
     pub keys: Vec<(Hotkey, WmCommand)>,
+    pub mouse: Vec<(MouseBinding, reactor::Command)>,
 }
 
 #[derive(Serialize, Deserialize, Default)]
@@ -66,6 +68,7 @@ pub struct Config {
 struct ConfigPartial {
     settings: SettingsPartial,
     keys: Option<FxHashMap<String, WmCommandOrDisable>>,
+    mouse: Option<FxHashMap<String, reactor::Command>>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -87,22 +90,115 @@ enum Disabled {
 #[serde(deny_unknown_fields)]
 pub struct Settings {
     pub animate: bool,
+    #[derive_args(AnimationConfigPartial)]
+    pub animation: AnimationConfig,
     pub default_disable: bool,
     pub mouse_follows_focus: bool,
     pub mouse_hides_on_focus: bool,
     pub focus_follows_mouse: bool,
+    pub focus_follows_mouse_delay_ms: u64,
+    pub focus_follows_mouse_speed_threshold: f64,
+    pub focus_mode: FocusMode,
+    pub focus_wrapping: WrapMode,
+    pub move_node_wrapping: WrapMode,
+    pub drag_swap_modifiers: ModifierKeys,
+    pub scroll_binding_sensitivity: f64,
     pub outer_gap: f64,
     pub inner_gap: f64,
+    pub accordion_peek: f64,
+    pub new_window_placement: TreePlacement,
+    pub new_window_weight: NewWindowWeight,
     pub default_keys: bool,
     pub default_layout_kind: LayoutKind,
     #[derive_args(GroupBarsPartial)]
     pub group_bars: GroupBars,
+    #[derive_args(AutoGroupPartial)]
+    pub auto_group: AutoGroup,
+    #[derive_args(SnapGridPartial)]
+    pub snap_grid: SnapGrid,
     #[derive_args(StatusIconPartial)]
     pub status_icon: StatusIcon,
     #[derive_args(ExperimentalPartial)]
     pub experimental: Experimental,
 }
 
+#[derive(PartialConfig!)]
+#[derive_args(AnimationConfigPartial)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct AnimationConfig {
+    /// Duration of the animation in seconds. Not used by spring easing.
+    pub duration: f64,
+    pub easing: Easing,
+    /// Bundle ids of apps whose windows are never animated.
+    pub disabled_apps: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Easing {
+    Linear,
+    EaseOutCubic,
+    Spring {
+        response: f64,
+        damping_fraction: f64,
+    },
+}
+
+impl Default for AnimationConfig {
+    fn default() -> Self {
+        Config::default().settings.animation
+    }
+}
+
+impl AnimationConfig {
+    pub fn validated(mut self) -> Self {
+        let default = Self::default();
+        self.duration = clamp_or(self.duration, 0.0, 5.0, default.duration);
+        if let Easing::Spring { response, damping_fraction } = &mut self.easing {
+            let Easing::Spring {
+                response: default_response,
+                damping_fraction: default_damping_fraction,
+            } = default.easing
+            else {
+                unreachable!("the default easing is a spring");
+            };
+            clamp_spring(
+                response,
+                damping_fraction,
+                default_response,
+                default_damping_fraction,
+            );
+        }
+        self
+    }
+
+    pub fn is_disabled_for(&self, bundle_id: Option<&str>) -> bool {
+        bundle_id.is_some_and(|id| self.disabled_apps.iter().any(|app| app == id))
+    }
+}
+
+/// Clamps the parameters of a spring, falling back to the defaults if they are
+/// NaN. A spring that is too stiff or never settles would animate forever.
+fn clamp_spring(
+    response: &mut f64,
+    damping_fraction: &mut f64,
+    default_response: f64,
+    default_damping_fraction: f64,
+) {
+    *response = clamp_or(*response, 0.01, 5.0, default_response);
+    *damping_fraction = clamp_or(*damping_fraction, 0.1, 2.0, default_damping_fraction);
+}
+
+/// Clamps `value` to `min..=max`, or returns `default` if it is NaN.
+fn clamp_or(value: f64, min: f64, max: f64, default: f64) -> f64 {
+    if value.is_nan() {
+        default
+    } else {
+        value.clamp(min, max)
+    }
+}
+
 #[derive(PartialConfig!)]
 #[derive_args(ExperimentalPartial)]
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
@@ -121,6 +217,55 @@ pub enum NewWindowPlacement {
     SameColumn,
 }
 
+/// How move_focus picks the window to focus.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum FocusMode {
+    /// Follow the structure of the layout.
+    #[default]
+    Tree,
+    /// Pick the nearest visible window on screen, including floating windows
+    /// and windows on other screens.
+    Geometric,
+}
+
+/// What happens when moving focus or a window past the edge of a layout.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum WrapMode {
+    /// Stay at the edge.
+    None,
+    /// Wrap around to the opposite edge of the same layout.
+    Workspace,
+    /// Continue on the next screen.
+    #[default]
+    Screen,
+}
+
+/// Where new windows go in tree layouts.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TreePlacement {
+    #[default]
+    AfterFocused,
+    BeforeFocused,
+    EndOfParent,
+    RootEnd,
+    UnderCursor,
+    LargestTile,
+}
+
+/// How much room a new window gets in tree layouts.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum NewWindowWeight {
+    /// The same share as the other windows in its container.
+    #[default]
+    Equal,
+    /// Half of the window it is placed next to.
+    Half,
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy, Default)]
 #[serde(rename_all = "snake_case")]
 pub enum CenterMode {
@@ -141,9 +286,32 @@ pub struct ScrollConfig {
     pub column_width_presets: Vec<f64>,
     pub new_window_in_column: NewWindowPlacement,
     pub scroll_sensitivity: f64,
+    pub scroll_modifiers: ModifierKeys,
     pub invert_scroll_direction: bool,
     pub infinite_loop: bool,
     pub single_column_aspect_ratio: String,
+    #[derive_args(ScrollAnimationPartial)]
+    pub animation: ScrollAnimation,
+}
+
+#[derive(PartialConfig!)]
+#[derive_args(ScrollAnimationPartial)]
+/// How the viewport moves when scrolling to a column.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
+#[serde(deny_unknown_fields)]
+pub struct ScrollAnimation {
+    /// Jump to the new position without animating.
+    pub instant: bool,
+    /// Roughly how long the spring takes to settle, in seconds.
+    pub response: f64,
+    /// 1.0 is critically damped; lower values overshoot the target.
+    pub damping_fraction: f64,
+}
+
+impl Default for ScrollAnimation {
+    fn default() -> Self {
+        Config::default().settings.experimental.scroll.animation
+    }
 }
 
 impl Default for ScrollConfig {
@@ -157,6 +325,13 @@ impl ScrollConfig {
         self.visible_columns = self.visible_columns.clamp(1, 5);
         self.scroll_sensitivity = self.scroll_sensitivity.clamp(0.0, 100.0);
         self.column_width_presets.retain(|&p| p > 0.0 && p <= 1.0);
+        let default = ScrollAnimation::default();
+        clamp_spring(
+            &mut self.animation.response,
+            &mut self.animation.damping_fraction,
+            default.response,
+            default.damping_fraction,
+        );
         self
     }
 
@@ -199,6 +374,142 @@ impl<'de> Deserialize<'de> for AspectRatio {
     }
 }
 
+/// A combination of modifier keys, written like "Alt + Shift".
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ModifierKeys {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    pub meta: bool,
+}
+
+impl ModifierKeys {
+    /// Whether every modifier in `other` is also held in `self`.
+    pub fn contains(self, other: ModifierKeys) -> bool {
+        (self.ctrl || !other.ctrl)
+            && (self.alt || !other.alt)
+            && (self.shift || !other.shift)
+            && (self.meta || !other.meta)
+    }
+}
+
+impl FromStr for ModifierKeys {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut keys = ModifierKeys::default();
+        if s.trim().is_empty() {
+            return Ok(keys);
+        }
+        for key in s.split('+').map(str::trim) {
+            let flag = match key {
+                "Ctrl" => &mut keys.ctrl,
+                "Alt" => &mut keys.alt,
+                "Shift" => &mut keys.shift,
+                "Meta" | "Cmd" => &mut keys.meta,
+                _ => return Err(format!("unknown modifier key: {key:?}")),
+            };
+            *flag = true;
+        }
+        Ok(keys)
+    }
+}
+
+impl TryFrom<String> for ModifierKeys {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<ModifierKeys> for String {
+    fn from(keys: ModifierKeys) -> String {
+        let names = [
+            (keys.ctrl, "Ctrl"),
+            (keys.alt, "Alt"),
+            (keys.shift, "Shift"),
+            (keys.meta, "Meta"),
+        ];
+        let names: Vec<_> = names.iter().filter(|(held, _)| *held).map(|(_, name)| *name).collect();
+        names.join(" + ")
+    }
+}
+
+/// Something done with the mouse that can be bound to a command.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum MouseAction {
+    ScrollUp,
+    ScrollDown,
+    ScrollLeft,
+    ScrollRight,
+    RightClick,
+    MiddleClick,
+}
+
+impl MouseAction {
+    const NAMES: [(MouseAction, &str); 6] = [
+        (MouseAction::ScrollUp, "ScrollUp"),
+        (MouseAction::ScrollDown, "ScrollDown"),
+        (MouseAction::ScrollLeft, "ScrollLeft"),
+        (MouseAction::ScrollRight, "ScrollRight"),
+        (MouseAction::RightClick, "RightClick"),
+        (MouseAction::MiddleClick, "MiddleClick"),
+    ];
+
+    pub fn is_scroll(self) -> bool {
+        !matches!(self, MouseAction::RightClick | MouseAction::MiddleClick)
+    }
+
+    fn name(self) -> &'static str {
+        Self::NAMES.iter().find(|(action, _)| *action == self).unwrap().1
+    }
+}
+
+/// A mouse action along with the modifier keys held during it, written like
+/// "Alt + ScrollUp".
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct MouseBinding {
+    pub modifiers: ModifierKeys,
+    pub action: MouseAction,
+}
+
+impl FromStr for MouseBinding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (modifiers, action) = match s.rsplit_once('+') {
+            Some((modifiers, action)) => (modifiers.parse()?, action.trim()),
+            None => (ModifierKeys::default(), s.trim()),
+        };
+        let Some(&(action, _)) = MouseAction::NAMES.iter().find(|(_, name)| *name == action) else {
+            return Err(format!("unknown mouse action: {action:?}"));
+        };
+        Ok(MouseBinding { modifiers, action })
+    }
+}
+
+impl TryFrom<String> for MouseBinding {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<MouseBinding> for String {
+    fn from(binding: MouseBinding) -> String {
+        let modifiers = String::from(binding.modifiers);
+        if modifiers.is_empty() {
+            binding.action.name().to_owned()
+        } else {
+            format!("{modifiers} + {}", binding.action.name())
+        }
+    }
+}
+
 #[derive(PartialConfig!)]
 #[derive_args(StatusIconPartial)]
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
@@ -251,6 +562,39 @@ impl GroupBars {
     }
 }
 
+#[derive(PartialConfig!)]
+#[derive_args(AutoGroupPartial)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct AutoGroup {
+    /// Group the windows of every app.
+    pub enable: bool,
+    /// Bundle ids of apps whose windows are grouped even if `enable` is off.
+    pub apps: Vec<String>,
+}
+
+impl Default for AutoGroup {
+    fn default() -> Self {
+        Config::default().settings.auto_group
+    }
+}
+
+#[derive(PartialConfig!)]
+#[derive_args(SnapGridPartial)]
+/// The grid that floating windows snap to.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
+#[serde(deny_unknown_fields)]
+pub struct SnapGrid {
+    pub columns: u32,
+    pub rows: u32,
+}
+
+impl AutoGroup {
+    pub fn is_enabled_for(&self, bundle_id: Option<&str>) -> bool {
+        self.enable || bundle_id.is_some_and(|id| self.apps.iter().any(|app| app == id))
+    }
+}
+
 impl ConfigPartial {
     fn default() -> Self {
         toml::from_str(include_str!("../glide.default.toml")).unwrap()
@@ -271,9 +615,18 @@ impl ConfigPartial {
             };
             keys.push((key, cmd));
         }
+        let mut mouse = Vec::new();
+        for (binding, cmd) in self.mouse.unwrap_or_default() {
+            let binding = MouseBinding::from_str(&binding).map_err(|e| SpannedError {
+                message: format!("Could not parse mouse binding: {binding}: {e}"),
+                span: None,
+            })?;
+            mouse.push((binding, cmd));
+        }
         Ok(Config {
             settings: self.settings.validate()?,
             keys,
+            mouse,
         })
     }
 
@@ -285,9 +638,12 @@ impl ConfigPartial {
                 Default::default()
             };
         keys.extend(high.keys.unwrap_or_default());
+        let mut mouse = low.mouse.unwrap_or_default();
+        mouse.extend(high.mouse.unwrap_or_default());
         Self {
             settings: SettingsPartial::merge(low.settings, high.settings),
             keys: Some(keys),
+            mouse: Some(mouse),
         }
     }
 }
@@ -323,6 +679,11 @@ impl Config {
         ConfigPartial::default().validate().unwrap()
     }
 
+    /// Returns the command bound to a mouse action, if any.
+    pub fn mouse_command(&self, binding: MouseBinding) -> Option<&reactor::Command> {
+        self.mouse.iter().find(|(b, _)| *b == binding).map(|(_, cmd)| cmd)
+    }
+
     fn parse(buf: &str) -> Result<Self, SpannedError> {
         let c: ConfigPartial = toml::from_str(buf)?;
         let defaults = ConfigPartial::default();
@@ -375,8 +736,9 @@ impl From<ValidationError> for SpannedError {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::actor::layout::LayoutCommand;
+    use crate::actor::layout::{LayoutCommand, PreselArg, PreselCancel};
     use crate::actor::reactor::Command as ReactorCommand;
+    use crate::model::Direction;
 
     #[test]
     fn default_config_is_valid() {
@@ -494,6 +856,194 @@ mod tests {
         assert!(config.keys.iter().any(|(hk, _)| hk.to_string() == "Alt + KeyJ"));
     }
 
+    #[test]
+    fn animation_easing_parses() {
+        let config = Config::parse(
+            r#"
+            [settings.animation]
+            easing = "linear"
+            disabled_apps = ["com.example.Slow"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.settings.animation.easing, Easing::Linear);
+        assert!(config.settings.animation.is_disabled_for(Some("com.example.Slow")));
+        assert!(!config.settings.animation.is_disabled_for(Some("com.example.Fast")));
+        assert!(!config.settings.animation.is_disabled_for(None));
+
+        let config = Config::parse(
+            r#"
+            [settings.animation]
+            easing = { spring = { response = 0.4, damping_fraction = 0.0 } }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.settings.animation.validated().easing,
+            Easing::Spring {
+                response: 0.4,
+                damping_fraction: 0.1
+            }
+        );
+
+        assert!(Config::parse("settings.animation.easing = \"bounce\"").is_err());
+
+        // Values that aren't numbers fall back to the defaults.
+        let config = Config::parse(
+            r#"
+            [settings.animation]
+            duration = nan
+            easing = { spring = { response = nan, damping_fraction = inf } }
+            "#,
+        )
+        .unwrap();
+        let animation = config.settings.animation.validated();
+        assert_eq!(animation.duration, 0.3);
+        assert_eq!(
+            animation.easing,
+            Easing::Spring {
+                response: 0.25,
+                damping_fraction: 2.0
+            }
+        );
+    }
+
+    #[test]
+    fn modifier_keys_parse() {
+        let config = Config::parse(r#"settings.drag_swap_modifiers = "Alt + Cmd""#).unwrap();
+        let keys = config.settings.drag_swap_modifiers;
+        assert_eq!(
+            keys,
+            ModifierKeys {
+                alt: true,
+                meta: true,
+                ..Default::default()
+            }
+        );
+        assert_eq!(String::from(keys), "Alt + Meta");
+        assert!(keys.contains(ModifierKeys {
+            alt: true,
+            ..Default::default()
+        }));
+        assert!(!keys.contains(ModifierKeys { shift: true, ..keys }));
+        assert!(keys.contains(ModifierKeys::default()));
+
+        let config = Config::parse("settings.drag_swap_modifiers = ''").unwrap();
+        assert_eq!(config.settings.drag_swap_modifiers, ModifierKeys::default());
+
+        assert!(Config::parse(r#"settings.drag_swap_modifiers = "Alt + Hyper""#).is_err());
+    }
+
+    #[test]
+    fn mouse_bindings_parse() {
+        let config = Config::parse(
+            r#"
+            [mouse]
+            "Alt + Shift + ScrollUp" = { move_focus = "up" }
+            "ScrollRight" = "debug"
+            "#,
+        )
+        .unwrap();
+        let alt_shift = ModifierKeys {
+            alt: true,
+            shift: true,
+            ..Default::default()
+        };
+        let scroll_up = MouseBinding {
+            modifiers: alt_shift,
+            action: MouseAction::ScrollUp,
+        };
+        assert!(matches!(
+            config.mouse_command(scroll_up),
+            Some(ReactorCommand::Layout(LayoutCommand::MoveFocus(Direction::Up)))
+        ));
+        assert_eq!(String::from(scroll_up), "Alt + Shift + ScrollUp");
+        // Modifiers have to match exactly.
+        let alt = ModifierKeys {
+            alt: true,
+            ..Default::default()
+        };
+        assert!(config.mouse_command(MouseBinding { modifiers: alt, ..scroll_up }).is_none());
+        let scroll_right = MouseBinding {
+            modifiers: ModifierKeys::default(),
+            action: MouseAction::ScrollRight,
+        };
+        assert!(config.mouse_command(scroll_right).is_some());
+        assert_eq!(String::from(scroll_right), "ScrollRight");
+
+        let config = Config::parse(
+            r#"
+            [mouse]
+            "Alt + ScrollUp" = { presel = "left" }
+            "Alt + ScrollDown" = { presel = { direction = "down", ratio = 0.3 } }
+            "Alt + ScrollLeft" = { presel = "cancel" }
+            "#,
+        )
+        .unwrap();
+        let presel = |action| {
+            let binding = MouseBinding { modifiers: alt, action };
+            match config.mouse_command(binding) {
+                Some(ReactorCommand::Layout(LayoutCommand::Presel(arg))) => *arg,
+                other => panic!("Unexpected command: {other:?}"),
+            }
+        };
+        assert_eq!(
+            presel(MouseAction::ScrollUp),
+            PreselArg::Direction(Direction::Left)
+        );
+        assert_eq!(
+            presel(MouseAction::ScrollDown),
+            PreselArg::WithRatio {
+                direction: Direction::Down,
+                ratio: 0.3
+            }
+        );
+        assert_eq!(
+            presel(MouseAction::ScrollLeft),
+            PreselArg::Cancel(PreselCancel::Cancel)
+        );
+
+        let config =
+            Config::parse(r#"mouse."Alt + RightClick" = "toggle_window_floating""#).unwrap();
+        let right_click: MouseBinding = "Alt + RightClick".parse().unwrap();
+        assert_eq!(right_click.action, MouseAction::RightClick);
+        assert!(!right_click.action.is_scroll());
+        assert!(matches!(
+            config.mouse_command(right_click),
+            Some(ReactorCommand::Layout(LayoutCommand::ToggleWindowFloating))
+        ));
+
+        assert!(Config::default().mouse.is_empty());
+        assert!(Config::parse(r#"mouse."Alt + Scroll" = "debug""#).is_err());
+        assert!(Config::parse(r#"mouse."Hyper + ScrollUp" = "debug""#).is_err());
+    }
+
+    #[test]
+    fn scroll_animation_is_validated() {
+        let config = Config::parse(
+            r#"
+            [settings.experimental.scroll]
+            animation.response = 0.0
+            animation.damping_fraction = 0.5
+            "#,
+        )
+        .unwrap();
+        let scroll = config.settings.experimental.scroll.validated();
+        assert_eq!(
+            scroll.animation,
+            ScrollAnimation {
+                instant: false,
+                response: 0.01,
+                damping_fraction: 0.5,
+            }
+        );
+
+        let config =
+            Config::parse("settings.experimental.scroll.animation.response = nan").unwrap();
+        let scroll = config.settings.experimental.scroll.validated();
+        assert_eq!(scroll.animation.response, 0.5);
+    }
+
     #[test]
     fn aspect_ratio_from_str_valid() {
         let ar = AspectRatio::from_str("16:9").unwrap();
@@ -4,6 +4,10 @@
 //! This module defines the [`LayoutTree`][layout_tree::LayoutTree] data
 //! structure, on which all layout logic is defined.
 
+pub mod drop_zone;
+pub mod floating;
+pub mod geometric_focus;
+pub mod hover_focus;
 mod layout_mapping;
 mod layout_tree;
 mod scroll_constraints;
@@ -13,8 +17,9 @@ mod size;
 pub mod spring;
 mod tree;
 mod window;
+pub mod window_animation;
 
 pub use layout_mapping::SpaceLayoutMapping;
-pub use layout_tree::{LayoutId, LayoutKind, LayoutTree};
+pub use layout_tree::{InsertionPoint, LayoutId, LayoutKind, LayoutTree, WindowSlot};
 pub use size::{ContainerKind, Direction, GroupBarInfo, Orientation};
 pub use tree::NodeId;
@@ -0,0 +1,130 @@
+// Copyright The Glide Authors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Decides where a window dragged onto another tile should go.
+
+use objc2_core_foundation::{CGPoint, CGRect};
+
+use super::Direction;
+
+/// The part of a tile a window was dropped on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DropZone {
+    /// Place the window on this side of the tile.
+    Edge(Direction),
+    /// Group the window with the tile.
+    Center,
+}
+
+/// Fraction of a tile's width and height, around its middle, that belongs to
+/// the center zone.
+const CENTER_FRACTION: f64 = 0.5;
+
+/// Returns the zone of `frame` that `point` falls in.
+///
+/// The middle of the frame is the center zone. The rest is divided between
+/// the edges by whichever one the point is closest to, relative to the size
+/// of the frame; ties go to the left and right edges. Returns `None` if the
+/// point is outside the frame or the frame has no area.
+pub fn drop_zone(frame: CGRect, point: CGPoint) -> Option<DropZone> {
+    // Written to also reject NaN.
+    if !(frame.size.width > 0.0 && frame.size.height > 0.0) {
+        return None;
+    }
+    // Position within the frame, from 0 to 1 on each axis.
+    let x = (point.x - frame.origin.x) / frame.size.width;
+    let y = (point.y - frame.origin.y) / frame.size.height;
+    let unit = 0.0..=1.0;
+    if !unit.contains(&x) || !unit.contains(&y) {
+        return None;
+    }
+
+    let margin = (1.0 - CENTER_FRACTION) / 2.0;
+    let center = margin..=1.0 - margin;
+    if center.contains(&x) && center.contains(&y) {
+        return Some(DropZone::Center);
+    }
+    [
+        (x, Direction::Left),
+        (1.0 - x, Direction::Right),
+        (y, Direction::Up),
+        (1.0 - y, Direction::Down),
+    ]
+    .into_iter()
+    .min_by(|(a, _), (b, _)| a.total_cmp(b))
+    .map(|(_, direction)| DropZone::Edge(direction))
+}
+
+#[cfg(test)]
+mod tests {
+    use objc2_core_foundation::CGSize;
+
+    use super::*;
+
+    fn rect(x: f64, y: f64, w: f64, h: f64) -> CGRect {
+        CGRect::new(CGPoint::new(x, y), CGSize::new(w, h))
+    }
+
+    fn zone(frame: CGRect, x: f64, y: f64) -> Option<DropZone> {
+        drop_zone(frame, CGPoint::new(x, y))
+    }
+
+    #[test]
+    fn it_divides_a_tile_into_five_zones() {
+        let frame = rect(100.0, 100.0, 400.0, 200.0);
+        assert_eq!(zone(frame, 300.0, 200.0), Some(DropZone::Center));
+        assert_eq!(zone(frame, 110.0, 200.0), Some(DropZone::Edge(Direction::Left)));
+        assert_eq!(zone(frame, 490.0, 200.0), Some(DropZone::Edge(Direction::Right)));
+        assert_eq!(zone(frame, 300.0, 110.0), Some(DropZone::Edge(Direction::Up)));
+        assert_eq!(zone(frame, 300.0, 290.0), Some(DropZone::Edge(Direction::Down)));
+        // Edges are relative to the size of the frame, so this is closer to the
+        // top even though it is fewer pixels from the left.
+        assert_eq!(zone(frame, 150.0, 110.0), Some(DropZone::Edge(Direction::Up)));
+    }
+
+    #[test]
+    fn it_includes_the_border_of_the_frame() {
+        let frame = rect(0.0, 0.0, 100.0, 100.0);
+        assert_eq!(zone(frame, 0.0, 50.0), Some(DropZone::Edge(Direction::Left)));
+        assert_eq!(zone(frame, 100.0, 50.0), Some(DropZone::Edge(Direction::Right)));
+        assert_eq!(zone(frame, 50.0, 100.0), Some(DropZone::Edge(Direction::Down)));
+        assert_eq!(zone(frame, 25.0, 25.0), Some(DropZone::Center));
+        assert_eq!(zone(frame, 75.0, 75.0), Some(DropZone::Center));
+    }
+
+    #[test]
+    fn corners_prefer_horizontal_edges() {
+        let frame = rect(0.0, 0.0, 100.0, 100.0);
+        assert_eq!(zone(frame, 0.0, 0.0), Some(DropZone::Edge(Direction::Left)));
+        assert_eq!(zone(frame, 100.0, 100.0), Some(DropZone::Edge(Direction::Right)));
+        let frame = rect(0.0, 0.0, 128.0, 128.0);
+        assert_eq!(zone(frame, 16.0, 112.0), Some(DropZone::Edge(Direction::Left)));
+    }
+
+    #[test]
+    fn points_outside_the_frame_have_no_zone() {
+        let frame = rect(0.0, 0.0, 100.0, 100.0);
+        assert_eq!(zone(frame, -0.5, 50.0), None);
+        assert_eq!(zone(frame, 50.0, 100.5), None);
+        assert_eq!(zone(frame, f64::NAN, 50.0), None);
+    }
+
+    #[test]
+    fn degenerate_frames_have_no_zones() {
+        assert_eq!(zone(rect(0.0, 0.0, 0.0, 0.0), 0.0, 0.0), None);
+        assert_eq!(zone(rect(0.0, 0.0, 0.0, 100.0), 0.0, 50.0), None);
+        assert_eq!(zone(rect(0.0, 0.0, 100.0, 0.0), 50.0, 0.0), None);
+        assert_eq!(zone(rect(100.0, 0.0, -100.0, 100.0), 50.0, 50.0), None);
+        assert_eq!(zone(rect(0.0, 0.0, f64::NAN, 100.0), 50.0, 50.0), None);
+        assert_eq!(zone(rect(f64::NAN, 0.0, 100.0, 100.0), 50.0, 50.0), None);
+    }
+
+    #[test]
+    fn thin_frames_still_have_every_zone() {
+        let frame = rect(0.0, 0.0, 1000.0, 1.0);
+        assert_eq!(zone(frame, 500.0, 0.5), Some(DropZone::Center));
+        assert_eq!(zone(frame, 500.0, 0.0), Some(DropZone::Edge(Direction::Up)));
+        assert_eq!(zone(frame, 500.0, 1.0), Some(DropZone::Edge(Direction::Down)));
+        assert_eq!(zone(frame, 10.0, 0.5), Some(DropZone::Edge(Direction::Left)));
+    }
+}
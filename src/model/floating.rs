@@ -0,0 +1,239 @@
+// Copyright The Glide Authors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Moves, resizes and snaps floating windows from the keyboard.
+
+use objc2_core_foundation::{CGPoint, CGRect, CGSize};
+use serde::{Deserialize, Serialize};
+
+use super::Direction;
+use crate::config::SnapGrid;
+
+/// Where to snap a floating window: a named position, or cells of the grid
+/// from the config.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(untagged)]
+pub enum Snap {
+    Position(SnapPosition),
+    Cells(GridCells),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapPosition {
+    LeftHalf,
+    RightHalf,
+    /// Keep the size of the window, but move it to the middle of the screen.
+    Center,
+    Maximize,
+    TopRightQuarter,
+}
+
+/// A block of cells in a grid, counting from 1 at the top left.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct GridCells {
+    pub column: u32,
+    pub row: u32,
+    /// Number of columns the window covers.
+    #[serde(default = "one")]
+    pub width: u32,
+    /// Number of rows the window covers.
+    #[serde(default = "one")]
+    pub height: u32,
+}
+
+fn one() -> u32 {
+    1
+}
+
+/// The smallest a floating window gets when resized from the keyboard.
+const MIN_SIZE: f64 = 50.0;
+
+/// Returns the frame for a window at `frame` snapped to `snap` within
+/// `area`, with `inner_gap` between cells of the grid.
+pub fn snap_frame(
+    snap: Snap,
+    frame: CGRect,
+    area: CGRect,
+    inner_gap: f64,
+    grid: &SnapGrid,
+) -> CGRect {
+    let cells = |columns, rows, column, row| {
+        let cells = GridCells {
+            column,
+            row,
+            width: 1,
+            height: 1,
+        };
+        cell_frame(area, inner_gap, columns, rows, cells)
+    };
+    match snap {
+        Snap::Position(SnapPosition::LeftHalf) => cells(2, 1, 1, 1),
+        Snap::Position(SnapPosition::RightHalf) => cells(2, 1, 2, 1),
+        Snap::Position(SnapPosition::Maximize) => cells(1, 1, 1, 1),
+        Snap::Position(SnapPosition::TopRightQuarter) => cells(2, 2, 2, 1),
+        Snap::Position(SnapPosition::Center) => {
+            let size = CGSize::new(
+                frame.size.width.min(area.size.width),
+                frame.size.height.min(area.size.height),
+            );
+            let origin =
+                CGPoint::new(area.mid().x - size.width / 2.0, area.mid().y - size.height / 2.0);
+            CGRect::new(origin, size)
+        }
+        Snap::Cells(cells) => cell_frame(area, inner_gap, grid.columns, grid.rows, cells),
+    }
+}
+
+/// Returns the frame of `cells` in a grid of `columns` by `rows` covering
+/// `area`. Cells outside the grid are moved inside it.
+fn cell_frame(area: CGRect, inner_gap: f64, columns: u32, rows: u32, cells: GridCells) -> CGRect {
+    // Returns the start and length of the cells along one axis.
+    let span = |start: f64, length: f64, count: u32, first: u32, covered: u32| {
+        let count = count.max(1);
+        let first = first.clamp(1, count);
+        let covered = covered.clamp(1, count - first + 1);
+        let cell = (length - inner_gap * f64::from(count - 1)) / f64::from(count);
+        (
+            start + f64::from(first - 1) * (cell + inner_gap),
+            cell * f64::from(covered) + inner_gap * f64::from(covered - 1),
+        )
+    };
+    let (x, width) = span(
+        area.origin.x,
+        area.size.width,
+        columns,
+        cells.column,
+        cells.width,
+    );
+    let (y, height) = span(area.origin.y, area.size.height, rows, cells.row, cells.height);
+    CGRect::new(CGPoint::new(x, y), CGSize::new(width, height))
+}
+
+/// Moves `frame` by `px` in `direction`, without going past the edge of
+/// `area` it is moving toward.
+pub fn move_frame(frame: CGRect, direction: Direction, px: f64, area: CGRect) -> CGRect {
+    let px = px.abs();
+    let CGPoint { x, y } = frame.origin;
+    let origin = match direction {
+        Direction::Left => CGPoint::new(x.min((x - px).max(area.min().x)), y),
+        Direction::Right => CGPoint::new(x.max((x + px).min(area.max().x - frame.size.width)), y),
+        Direction::Up => CGPoint::new(x, y.min((y - px).max(area.min().y))),
+        Direction::Down => CGPoint::new(x, y.max((y + px).min(area.max().y - frame.size.height))),
+    };
+    CGRect::new(origin, frame.size)
+}
+
+/// Moves the edge of `frame` in `direction` outward by `px`, or inward if
+/// `px` is negative, without growing past the edge of `area`.
+pub fn resize_frame(frame: CGRect, direction: Direction, px: f64, area: CGRect) -> CGRect {
+    let room = match direction {
+        Direction::Left => frame.min().x - area.min().x,
+        Direction::Right => area.max().x - frame.max().x,
+        Direction::Up => frame.min().y - area.min().y,
+        Direction::Down => area.max().y - frame.max().y,
+    };
+    let px = px.min(room.max(0.0));
+    let CGRect { mut origin, mut size } = frame;
+    match direction {
+        Direction::Left | Direction::Right => {
+            size.width = (size.width + px).max(MIN_SIZE.min(size.width));
+            if direction == Direction::Left {
+                origin.x = frame.max().x - size.width;
+            }
+        }
+        Direction::Up | Direction::Down => {
+            size.height = (size.height + px).max(MIN_SIZE.min(size.height));
+            if direction == Direction::Up {
+                origin.y = frame.max().y - size.height;
+            }
+        }
+    }
+    CGRect::new(origin, size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: i32, y: i32, w: i32, h: i32) -> CGRect {
+        CGRect::new(
+            CGPoint::new(f64::from(x), f64::from(y)),
+            CGSize::new(f64::from(w), f64::from(h)),
+        )
+    }
+
+    #[test]
+    fn snapping_leaves_gaps_between_cells() {
+        let area = rect(10, 10, 1010, 510);
+        let frame = rect(0, 0, 200, 100);
+        let grid = SnapGrid { columns: 3, rows: 2 };
+        let snap = |snap| snap_frame(snap, frame, area, 10.0, &grid);
+        use SnapPosition::*;
+
+        assert_eq!(rect(10, 10, 500, 510), snap(Snap::Position(LeftHalf)));
+        assert_eq!(rect(520, 10, 500, 510), snap(Snap::Position(RightHalf)));
+        assert_eq!(rect(10, 10, 1010, 510), snap(Snap::Position(Maximize)));
+        assert_eq!(rect(520, 10, 500, 250), snap(Snap::Position(TopRightQuarter)));
+        assert_eq!(rect(415, 215, 200, 100), snap(Snap::Position(Center)));
+
+        let cells = |column, row, width, height| {
+            snap(Snap::Cells(GridCells { column, row, width, height }))
+        };
+        assert_eq!(rect(10, 10, 330, 250), cells(1, 1, 1, 1));
+        assert_eq!(rect(350, 270, 670, 250), cells(2, 2, 2, 1));
+        // Cells past the edge of the grid are kept inside it.
+        assert_eq!(rect(690, 270, 330, 250), cells(5, 5, 1, 1));
+        assert_eq!(rect(350, 10, 670, 510), cells(2, 1, 5, 5));
+    }
+
+    #[test]
+    fn moving_stops_at_the_edge() {
+        let area = rect(0, 0, 1000, 500);
+        let frame = rect(100, 100, 200, 100);
+        assert_eq!(
+            rect(150, 100, 200, 100),
+            move_frame(frame, Direction::Right, 50.0, area)
+        );
+        assert_eq!(
+            rect(0, 100, 200, 100),
+            move_frame(frame, Direction::Left, 500.0, area)
+        );
+        assert_eq!(
+            rect(100, 0, 200, 100),
+            move_frame(frame, Direction::Up, 500.0, area)
+        );
+        assert_eq!(
+            rect(100, 400, 200, 100),
+            move_frame(frame, Direction::Down, 500.0, area)
+        );
+        // Windows that are already past the edge don't jump back.
+        let outside = rect(900, 100, 200, 100);
+        assert_eq!(outside, move_frame(outside, Direction::Right, 50.0, area));
+        assert_eq!(
+            rect(850, 100, 200, 100),
+            move_frame(outside, Direction::Left, 50.0, area)
+        );
+    }
+
+    #[test]
+    fn resizing_moves_one_edge() {
+        let area = rect(0, 0, 1000, 500);
+        let frame = rect(100, 100, 200, 100);
+        let resize = |direction, px| resize_frame(frame, direction, px, area);
+        assert_eq!(rect(100, 100, 250, 100), resize(Direction::Right, 50.0));
+        assert_eq!(rect(50, 100, 250, 100), resize(Direction::Left, 50.0));
+        assert_eq!(rect(100, 50, 200, 150), resize(Direction::Up, 50.0));
+        assert_eq!(rect(100, 100, 200, 75), resize(Direction::Down, -25.0));
+        // Windows can't be shrunk to nothing.
+        assert_eq!(rect(250, 100, 50, 100), resize(Direction::Left, -500.0));
+        // Or grown past the edge of the area.
+        assert_eq!(rect(100, 100, 900, 100), resize(Direction::Right, 5000.0));
+        assert_eq!(rect(0, 100, 300, 100), resize(Direction::Left, 5000.0));
+        assert_eq!(rect(100, 100, 200, 400), resize(Direction::Down, 5000.0));
+        // A window already past the edge isn't pulled back in.
+        let outside = rect(900, 100, 200, 100);
+        assert_eq!(outside, resize_frame(outside, Direction::Right, 50.0, area));
+    }
+}
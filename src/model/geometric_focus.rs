@@ -0,0 +1,143 @@
+// Copyright The Glide Authors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Picks the window to focus in a direction based on where windows are on
+//! screen, rather than on the structure of the layout.
+
+use objc2_core_foundation::CGRect;
+
+use super::Direction;
+
+/// Returns the candidate nearest to `from` in `direction`.
+///
+/// Only candidates whose center is past the center of `from` in that
+/// direction are considered. Candidates that line up with `from`, sharing
+/// some of its extent across the direction of travel, are preferred; ones
+/// that don't must also reach past the far edge of `from`. After that the
+/// candidate with the smallest gap to `from` wins, then the one whose center
+/// is closest across the direction of travel. Ties go to the first
+/// candidate.
+pub fn nearest_in_direction<T>(
+    from: CGRect,
+    direction: Direction,
+    candidates: impl IntoIterator<Item = (T, CGRect)>,
+) -> Option<T> {
+    let (from_along, from_across) = project(from, direction);
+    candidates
+        .into_iter()
+        .filter_map(|(item, frame)| {
+            let (along, across) = project(frame, direction);
+            if mid(along) <= mid(from_along) {
+                return None;
+            }
+            let lines_up = f64::min(across.1, from_across.1) > f64::max(across.0, from_across.0);
+            if !lines_up && along.1 <= from_along.1 {
+                return None;
+            }
+            let gap = f64::max(along.0 - from_along.1, 0.0);
+            let offset = (mid(across) - mid(from_across)).abs();
+            Some((!lines_up, gap, offset, item))
+        })
+        .min_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)).then(a.2.total_cmp(&b.2)))
+        .map(|(.., item)| item)
+}
+
+/// Returns the extent of `rect` along and across `direction`, flipped so that
+/// the direction of travel always points toward larger values.
+fn project(rect: CGRect, direction: Direction) -> ((f64, f64), (f64, f64)) {
+    let (min, max) = (rect.min(), rect.max());
+    match direction {
+        Direction::Right => ((min.x, max.x), (min.y, max.y)),
+        Direction::Left => ((-max.x, -min.x), (min.y, max.y)),
+        Direction::Down => ((min.y, max.y), (min.x, max.x)),
+        Direction::Up => ((-max.y, -min.y), (min.x, max.x)),
+    }
+}
+
+fn mid((min, max): (f64, f64)) -> f64 {
+    (min + max) / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use objc2_core_foundation::{CGPoint, CGSize};
+
+    use super::*;
+
+    fn rect(x: i32, y: i32, w: i32, h: i32) -> CGRect {
+        CGRect::new(
+            CGPoint::new(f64::from(x), f64::from(y)),
+            CGSize::new(f64::from(w), f64::from(h)),
+        )
+    }
+
+    #[test]
+    fn picks_the_nearest_window_that_lines_up() {
+        use Direction::*;
+        // A column on the left, two rows on the right, and a small floating
+        // window overlapping the bottom row.
+        let windows = [
+            (1, rect(0, 0, 100, 200)),
+            (2, rect(100, 0, 100, 100)),
+            (3, rect(100, 100, 100, 100)),
+            (4, rect(150, 150, 40, 40)),
+        ];
+        let from = |id: usize| windows[id - 1].1;
+        let nearest = |id, direction| {
+            nearest_in_direction(
+                from(id),
+                direction,
+                windows.iter().copied().filter(|&(other, _)| other != id),
+            )
+        };
+
+        assert_eq!(Some(2), nearest(1, Right));
+        assert_eq!(None, nearest(1, Left));
+        assert_eq!(None, nearest(1, Up));
+        assert_eq!(Some(1), nearest(2, Left));
+        assert_eq!(Some(3), nearest(2, Down));
+        assert_eq!(Some(1), nearest(3, Left));
+        assert_eq!(Some(4), nearest(3, Right));
+        assert_eq!(Some(4), nearest(3, Down));
+        assert_eq!(Some(3), nearest(4, Left));
+        assert_eq!(Some(3), nearest(4, Up));
+    }
+
+    #[test]
+    fn prefers_windows_that_line_up_over_closer_ones() {
+        let from = rect(0, 0, 100, 100);
+        let candidates = [
+            ("diagonal", rect(100, 100, 100, 100)),
+            ("far", rect(500, 50, 100, 100)),
+            ("farther", rect(600, 0, 100, 100)),
+        ];
+        assert_eq!(
+            Some("far"),
+            nearest_in_direction(from, Direction::Right, candidates)
+        );
+        // Windows that don't line up are still reachable.
+        assert_eq!(
+            Some("diagonal"),
+            nearest_in_direction(from, Direction::Right, candidates.into_iter().take(1))
+        );
+    }
+
+    #[test]
+    fn reaches_other_screens() {
+        // A taller screen to the right that extends above the left one.
+        let windows = [
+            ("left screen", rect(0, 0, 1000, 800)),
+            ("right screen top", rect(1000, -200, 800, 500)),
+            ("right screen bottom", rect(1000, 300, 800, 500)),
+        ];
+        let from = windows[0].1;
+        assert_eq!(
+            Some("right screen bottom"),
+            nearest_in_direction(from, Direction::Right, windows.into_iter().skip(1))
+        );
+        assert_eq!(
+            Some("left screen"),
+            nearest_in_direction(windows[1].1, Direction::Left, windows)
+        );
+    }
+}
@@ -0,0 +1,158 @@
+// Copyright The Glide Authors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Decides when focus should follow the mouse to the window under it.
+//!
+//! Like the animations, nothing here reads the clock. The caller passes in the
+//! current time and polls for a window to focus once the deadline is reached.
+
+use std::time::{Duration, Instant};
+
+use objc2_core_foundation::CGPoint;
+
+/// Waits for the mouse to rest over a window before focusing it, so that
+/// moving the mouse across windows on the way somewhere else doesn't steal
+/// focus from each of them.
+#[derive(Debug)]
+pub struct HoverFocus<T> {
+    delay: Duration,
+    speed_threshold: f64,
+    pending: Option<(T, Instant)>,
+}
+
+impl<T: Copy> HoverFocus<T> {
+    /// Windows are focused after the mouse stays over them for `delay`.
+    /// Entering a window slower than `speed_threshold`, in points per second,
+    /// focuses it right away; a threshold of zero always waits.
+    pub fn new(delay: Duration, speed_threshold: f64) -> Self {
+        HoverFocus {
+            delay,
+            speed_threshold,
+            pending: None,
+        }
+    }
+
+    pub fn set_config(&mut self, delay: Duration, speed_threshold: f64) {
+        self.delay = delay;
+        self.speed_threshold = speed_threshold;
+    }
+
+    /// Called when the mouse moves over `target` at `speed`. Returns the
+    /// target if it should be focused now.
+    pub fn entered(&mut self, target: T, speed: f64, now: Instant) -> Option<T> {
+        if self.delay.is_zero() || speed < self.speed_threshold {
+            self.pending = None;
+            return Some(target);
+        }
+        self.pending = Some((target, now + self.delay));
+        None
+    }
+
+    /// Called when the mouse leaves for something that can't be focused.
+    pub fn cancel(&mut self) {
+        self.pending = None;
+    }
+
+    /// Returns the pending target if the mouse has stayed over it long enough.
+    pub fn poll(&mut self, now: Instant) -> Option<T> {
+        let (target, due) = self.pending?;
+        if now < due {
+            return None;
+        }
+        self.pending = None;
+        Some(target)
+    }
+
+    /// When [`poll`][Self::poll] should next be called, if anything is pending.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.pending.map(|(_, due)| due)
+    }
+}
+
+/// Tracks how fast the mouse is moving from the positions it reports.
+#[derive(Debug, Default)]
+pub struct MouseSpeed {
+    last: Option<(CGPoint, Instant)>,
+}
+
+impl MouseSpeed {
+    /// Records a new position and returns the speed since the last one, in
+    /// points per second.
+    pub fn update(&mut self, point: CGPoint, now: Instant) -> f64 {
+        let Some((last_point, last_time)) = self.last.replace((point, now)) else {
+            return 0.0;
+        };
+        let elapsed = now.saturating_duration_since(last_time).as_secs_f64();
+        if elapsed == 0.0 {
+            return 0.0;
+        }
+        (point.x - last_point.x).hypot(point.y - last_point.y) / elapsed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DELAY: Duration = Duration::from_millis(100);
+
+    #[test]
+    fn it_focuses_right_away_without_a_delay() {
+        let now = Instant::now();
+        let mut focus = HoverFocus::new(Duration::ZERO, 0.0);
+        assert_eq!(focus.entered(1, 5000.0, now), Some(1));
+        assert_eq!(focus.deadline(), None);
+    }
+
+    #[test]
+    fn it_waits_for_the_mouse_to_rest() {
+        let now = Instant::now();
+        let mut focus = HoverFocus::new(DELAY, 0.0);
+        assert_eq!(focus.entered(1, 0.0, now), None);
+        assert_eq!(focus.deadline(), Some(now + DELAY));
+        assert_eq!(focus.poll(now + DELAY / 2), None);
+        assert_eq!(focus.poll(now + DELAY), Some(1));
+        assert_eq!(focus.poll(now + DELAY * 2), None);
+        assert_eq!(focus.deadline(), None);
+    }
+
+    #[test]
+    fn moving_to_another_window_restarts_the_delay() {
+        let now = Instant::now();
+        let mut focus = HoverFocus::new(DELAY, 0.0);
+        assert_eq!(focus.entered(1, 0.0, now), None);
+        assert_eq!(focus.entered(2, 0.0, now + DELAY / 2), None);
+        assert_eq!(focus.poll(now + DELAY), None);
+        assert_eq!(focus.poll(now + DELAY * 3 / 2), Some(2));
+    }
+
+    #[test]
+    fn leaving_cancels_the_pending_focus() {
+        let now = Instant::now();
+        let mut focus = HoverFocus::new(DELAY, 0.0);
+        assert_eq!(focus.entered(1, 0.0, now), None);
+        focus.cancel();
+        assert_eq!(focus.deadline(), None);
+        assert_eq!(focus.poll(now + DELAY), None);
+    }
+
+    #[test]
+    fn slow_movement_skips_the_delay() {
+        let now = Instant::now();
+        let mut focus = HoverFocus::new(DELAY, 200.0);
+        assert_eq!(focus.entered(1, 1000.0, now), None);
+        // A slow entry also replaces anything pending.
+        assert_eq!(focus.entered(2, 100.0, now), Some(2));
+        assert_eq!(focus.poll(now + DELAY), None);
+    }
+
+    #[test]
+    fn mouse_speed_is_measured_between_positions() {
+        let now = Instant::now();
+        let mut speed = MouseSpeed::default();
+        assert_eq!(speed.update(CGPoint::new(0.0, 0.0), now), 0.0);
+        let later = now + Duration::from_millis(500);
+        assert_eq!(speed.update(CGPoint::new(30.0, 40.0), later), 100.0);
+        assert_eq!(speed.update(CGPoint::new(0.0, 0.0), later), 0.0);
+    }
+}
@@ -7,12 +7,13 @@ use objc2_core_foundation::CGRect;
 use serde::{Deserialize, Serialize};
 use tracing::warn;
 
+use super::drop_zone::DropZone;
 use super::selection::Selection;
-use super::size::{ContainerKind, Direction, Size};
+use super::size::{ContainerKind, Direction, Orientation, Size};
 use super::tree::{self, Tree};
 use super::window::Window;
 use crate::actor::app::{WindowId, pid_t};
-use crate::config::Config;
+use crate::config::{Config, NewWindowWeight};
 use crate::model::tree::{NodeId, NodeMap, OwnedNode};
 
 /// The layout tree.
@@ -39,6 +40,33 @@ slotmap::new_key_type! {
     pub struct LayoutId;
 }
 
+/// Where a window was in a layout before it was detached with
+/// [`LayoutTree::detach_window`].
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct WindowSlot {
+    layout: LayoutId,
+    /// The parent of the window, followed by the rest of its ancestors.
+    ancestors: Vec<NodeId>,
+    prev_sibling: Option<NodeId>,
+    next_sibling: Option<NodeId>,
+    weight: f32,
+}
+
+/// Where to add a window with [`LayoutTree::add_window_at`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InsertionPoint {
+    Before(NodeId),
+    After(NodeId),
+    /// At the end of this container.
+    End(NodeId),
+}
+
+impl WindowSlot {
+    pub fn layout(&self) -> LayoutId {
+        self.layout
+    }
+}
+
 impl LayoutTree {
     pub fn new() -> LayoutTree {
         LayoutTree {
@@ -61,10 +89,11 @@ impl LayoutTree {
         let id = self.layout_roots.insert(root);
         self.layout_kinds.insert(id, kind);
         if kind == LayoutKind::Scroll {
-            self.tree
-                .data
-                .size
-                .set_kind(self.layout_roots[id].id(), ContainerKind::Horizontal);
+            self.tree.data.size.set_kind(
+                self.layout_roots[id].id(),
+                ContainerKind::Horizontal,
+                &self.tree.map,
+            );
         }
         id
     }
@@ -131,7 +160,7 @@ impl LayoutTree {
                     .unwrap_or(selection);
                 self.tree.mk_node().insert_after(parent)
             };
-            self.tree.data.size.set_kind(column, ContainerKind::Vertical);
+            self.tree.data.size.set_kind(column, ContainerKind::Vertical, &self.tree.map);
             self.tree.data.size.set_weight(column, weight, &self.tree.map);
             let node = self.tree.mk_node().push_back(column);
             self.tree.data.window.set_window(layout, node, wid);
@@ -143,7 +172,7 @@ impl LayoutTree {
                 .unwrap_or(root);
             if column == root {
                 let col = self.tree.mk_node().push_back(root);
-                self.tree.data.size.set_kind(col, ContainerKind::Vertical);
+                self.tree.data.size.set_kind(col, ContainerKind::Vertical, &self.tree.map);
                 self.tree.data.size.set_weight(col, weight, &self.tree.map);
                 let node = self.tree.mk_node().push_back(col);
                 self.tree.data.window.set_window(layout, node, wid);
@@ -204,6 +233,122 @@ impl LayoutTree {
         node
     }
 
+    /// Adds a window at `point`, taking room from its new siblings according to
+    /// `weight`.
+    ///
+    /// Windows can't be siblings of the root, so points before or after the
+    /// root are treated as the end of it.
+    pub fn add_window_at(
+        &mut self,
+        layout: LayoutId,
+        point: InsertionPoint,
+        wid: WindowId,
+        weight: NewWindowWeight,
+    ) -> NodeId {
+        let map = &self.tree.map;
+        let point = match point {
+            InsertionPoint::Before(node) | InsertionPoint::After(node)
+                if node.parent(map).is_none() =>
+            {
+                InsertionPoint::End(node)
+            }
+            point => point,
+        };
+        let (parent, neighbor) = match point {
+            InsertionPoint::Before(node) | InsertionPoint::After(node) => {
+                (node.parent(map).unwrap(), Some(node))
+            }
+            InsertionPoint::End(parent) => (parent, parent.last_child(map)),
+        };
+        let num_children = parent.children(map).count();
+        let total = self.tree.data.size.total(parent);
+
+        let node = match point {
+            InsertionPoint::Before(node) => self.tree.mk_node().insert_before(node),
+            InsertionPoint::After(node) => self.tree.mk_node().insert_after(node),
+            InsertionPoint::End(parent) => self.tree.mk_node().push_back(parent),
+        };
+        self.tree.data.window.set_window(layout, node, wid);
+        match (weight, neighbor) {
+            (NewWindowWeight::Equal, _) if num_children > 0 => {
+                let share = (total / num_children as f64) as f32;
+                self.tree.data.size.set_weight(node, share, &self.tree.map);
+            }
+            (NewWindowWeight::Half, Some(neighbor)) => {
+                let share = self.tree.data.size.weight(neighbor) / 2.0;
+                self.tree.data.size.set_weight(neighbor, share, &self.tree.map);
+                self.tree.data.size.set_weight(node, share, &self.tree.map);
+            }
+            _ => (),
+        }
+        node
+    }
+
+    /// Returns the visible window that takes up the largest share of the
+    /// layout, ignoring gaps. Ties go to the first window.
+    pub fn largest_window(&self, layout: LayoutId) -> Option<NodeId> {
+        let map = &self.tree.map;
+        let mut largest: Option<(NodeId, f64)> = None;
+        let mut stack = vec![(self.root(layout), 1.0)];
+        while let Some((node, share)) = stack.pop() {
+            if self.window_at(node).is_some() {
+                if largest.is_none_or(|(_, largest)| share > largest) {
+                    largest = Some((node, share));
+                }
+            } else if self.container_kind(node).is_split() {
+                for child in node.children_rev(map) {
+                    let proportion = self.tree.data.size.proportion(map, child).unwrap_or(0.0);
+                    stack.push((child, share * proportion));
+                }
+            } else {
+                // Only the selected child of groups and accordions is visible,
+                // or nearly so.
+                let selected = self.tree.data.selection.last_selection(map, node);
+                stack.extend(selected.map(|child| (child, share)));
+            }
+        }
+        largest.map(|(node, _)| node)
+    }
+
+    /// Adds a window on the given side of `target`, splitting `target` if its
+    /// parent isn't already oriented that way. The new window takes `ratio` of
+    /// the space `target` had.
+    ///
+    /// If `target` is a container without any windows, like the root of an
+    /// empty layout, the window is added to it instead.
+    pub fn add_window_beside(
+        &mut self,
+        layout: LayoutId,
+        target: NodeId,
+        wid: WindowId,
+        direction: Direction,
+        ratio: f64,
+    ) -> NodeId {
+        if target
+            .traverse_preorder(&self.tree.map)
+            .all(|node| self.window_at(node).is_none())
+        {
+            return self.add_window_under(layout, target, wid);
+        }
+        let kind = ContainerKind::from(direction.orientation());
+        if target
+            .parent(&self.tree.map)
+            .is_none_or(|parent| self.container_kind(parent) != kind)
+        {
+            self.nest_in_container(layout, target, kind);
+        }
+        let node = match direction {
+            Direction::Left | Direction::Up => self.tree.mk_node().insert_before(target),
+            Direction::Right | Direction::Down => self.tree.mk_node().insert_after(target),
+        };
+        self.tree.data.window.set_window(layout, node, wid);
+        let weight = self.tree.data.size.weight(target);
+        let ratio = ratio as f32;
+        self.tree.data.size.set_weight(target, weight * (1.0 - ratio), &self.tree.map);
+        self.tree.data.size.set_weight(node, weight * ratio, &self.tree.map);
+        node
+    }
+
     pub fn move_node_after(&mut self, sibling: NodeId, moving_node: NodeId) {
         let map = &self.tree.map;
         let Some(old_parent) = moving_node.parent(map) else {
@@ -246,6 +391,63 @@ impl LayoutTree {
         }
     }
 
+    /// Removes a window from every layout, returning where it was so that it
+    /// can be put back later with [`Self::restore_window`].
+    pub fn detach_window(&mut self, wid: WindowId) -> Vec<WindowSlot> {
+        let nodes = self.tree.data.window.take_nodes_for(wid).collect::<Vec<_>>();
+        let mut slots = Vec::with_capacity(nodes.len());
+        for (layout, node) in nodes {
+            let map = &self.tree.map;
+            slots.push(WindowSlot {
+                layout,
+                ancestors: node.ancestors(map).skip(1).collect(),
+                prev_sibling: node.prev_sibling(map),
+                next_sibling: node.next_sibling(map),
+                weight: self.tree.data.size.weight(node),
+            });
+            node.detach(&mut self.tree).remove();
+        }
+        slots
+    }
+
+    /// Puts a detached window back where it was.
+    ///
+    /// If its old parent was removed in the meantime the window goes in the
+    /// nearest surviving ancestor instead, next to one of its old siblings if
+    /// possible. Returns None if there is nowhere suitable to put it, which
+    /// includes the column of a scroll layout having been removed.
+    pub fn restore_window(&mut self, wid: WindowId, slot: &WindowSlot) -> Option<NodeId> {
+        let layout = slot.layout;
+        if !self.layout_roots.contains_key(layout) {
+            return None;
+        }
+        if let Some(node) = self.window_node(layout, wid) {
+            return Some(node);
+        }
+        let map = &self.tree.map;
+        let parent = slot.ancestors.iter().copied().find(|&node| map.contains(node))?;
+        if parent.ancestors(map).last() != Some(self.root(layout)) {
+            return None;
+        }
+        let is_old_parent = slot.ancestors.first() == Some(&parent);
+        if !is_old_parent && self.is_scroll_root(parent) {
+            return None;
+        }
+        let is_sibling = |node: &NodeId| map.contains(*node) && node.parent(map) == Some(parent);
+        let prev = slot.prev_sibling.filter(is_sibling);
+        let next = slot.next_sibling.filter(is_sibling);
+        let node = match (prev, next) {
+            (Some(prev), _) => self.tree.mk_node().insert_after(prev),
+            (None, Some(next)) => self.tree.mk_node().insert_before(next),
+            (None, None) => self.tree.mk_node().push_back(parent),
+        };
+        self.tree.data.window.set_window(layout, node, wid);
+        if is_old_parent {
+            self.tree.data.size.set_weight(node, slot.weight, &self.tree.map);
+        }
+        Some(node)
+    }
+
     pub fn remove_windows_for_app(&mut self, pid: pid_t) {
         for (_, _, node) in self.tree.data.window.take_nodes_for_app(pid) {
             node.detach(&mut self.tree).remove();
@@ -315,7 +517,7 @@ impl LayoutTree {
     #[allow(dead_code)]
     pub fn add_container(&mut self, parent: NodeId, kind: ContainerKind) -> NodeId {
         let node = self.tree.mk_node().push_back(parent);
-        self.tree.data.size.set_kind(node, kind);
+        self.tree.data.size.set_kind(node, kind, &self.tree.map);
         node
     }
 
@@ -346,7 +548,7 @@ impl LayoutTree {
     }
 
     pub fn set_fullscreen(&mut self, node: NodeId, is_fullscreen: bool) {
-        self.tree.data.size.set_fullscreen(node, is_fullscreen)
+        self.tree.data.size.set_fullscreen(node, is_fullscreen, &self.tree.map)
     }
 
     pub fn is_fullscreen(&mut self, node: NodeId) -> bool {
@@ -415,6 +617,16 @@ impl LayoutTree {
         .last()
     }
 
+    /// Like [`Self::traverse`], but when there is nothing in `direction`,
+    /// wraps around to the node farthest away in the opposite direction.
+    pub fn traverse_wrapping(&self, from: NodeId, direction: Direction) -> Option<NodeId> {
+        self.traverse(from, direction).or_else(|| {
+            let opposite = direction.opposite();
+            let far = iter::successors(Some(from), |&n| self.traverse(n, opposite)).last()?;
+            (far != from).then_some(far)
+        })
+    }
+
     pub fn traverse_scroll_wrapping(
         &self,
         layout: LayoutId,
@@ -454,6 +666,44 @@ impl LayoutTree {
         Some(node)
     }
 
+    /// Returns the window `step` windows away from the one at `from`, in the
+    /// order windows appear in the layout, wrapping around. If `from` is not
+    /// a window, starts from the first or last window.
+    pub fn cycle_windows(&self, layout: LayoutId, from: NodeId, step: isize) -> Option<NodeId> {
+        let map = self.map();
+        let windows: Vec<_> = (self.root(layout).traverse_preorder(map))
+            .filter(|&node| self.window_at(node).is_some())
+            .collect();
+        let len = windows.len() as isize;
+        let idx = match windows.iter().position(|&node| node == from) {
+            Some(idx) => (idx as isize + step).rem_euclid(len),
+            None if step > 0 => step - 1,
+            None => len + step,
+        };
+        let target = *windows.get(usize::try_from(idx).ok()?)?;
+        (target != from).then_some(target)
+    }
+
+    /// Returns the nearest group that `node` is in, along with the index of
+    /// the tab that contains `node`.
+    pub fn enclosing_group(&self, node: NodeId) -> Option<(NodeId, usize)> {
+        let map = self.map();
+        node.ancestors_with_parent(map).find_map(|(node, parent)| {
+            let parent = parent.filter(|&p| self.container_kind(p).is_group())?;
+            Some((parent, parent.children(map).position(|child| child == node)?))
+        })
+    }
+
+    /// Follows the selection down from `node` as far as it goes.
+    pub fn selected_leaf(&self, node: NodeId) -> NodeId {
+        let map = self.map();
+        iter::successors(Some(node), |&node| {
+            self.tree.data.selection.local_selection(map, node).or(node.first_child(map))
+        })
+        .last()
+        .unwrap()
+    }
+
     pub fn select_returning_surfaced_windows(&mut self, selection: NodeId) -> Vec<WindowId> {
         let map = &self.tree.map;
         let mut highest_revealed = selection;
@@ -529,6 +779,32 @@ impl LayoutTree {
         moved
     }
 
+    /// Like [`Self::move_node`], but when the node is already at the edge of
+    /// the layout, moves it to the opposite edge instead.
+    pub fn move_node_wrapping(
+        &mut self,
+        layout: LayoutId,
+        moving_node: NodeId,
+        direction: Direction,
+    ) -> bool {
+        if self.move_node(layout, moving_node, direction) {
+            return true;
+        }
+        // move_node only gives up on children of a root that is already
+        // oriented in this direction.
+        let root = self.root(layout);
+        if moving_node.parent(self.map()) != Some(root) {
+            return false;
+        }
+        match direction {
+            Direction::Right | Direction::Down => {
+                moving_node.detach(&mut self.tree).push_front(root)
+            }
+            Direction::Left | Direction::Up => moving_node.detach(&mut self.tree).push_back(root),
+        };
+        true
+    }
+
     fn move_node_inner(
         &mut self,
         layout: LayoutId,
@@ -615,6 +891,68 @@ impl LayoutTree {
         true
     }
 
+    /// Moves `node` next to `target` according to where it was dropped.
+    ///
+    /// Dropping on an edge puts the node on that side of the target, splitting
+    /// the target if its parent isn't already oriented that way. Dropping in
+    /// the center adds the node to the target's group, or creates a tabbed
+    /// group if the target isn't in one.
+    pub fn drop_node(
+        &mut self,
+        layout: LayoutId,
+        node: NodeId,
+        target: NodeId,
+        zone: DropZone,
+    ) -> bool {
+        if node == target || node.parent(&self.tree.map).is_none() {
+            return false;
+        }
+        let (kind, before) = match zone {
+            DropZone::Center => (ContainerKind::Tabbed, false),
+            DropZone::Edge(direction) => (
+                ContainerKind::from(direction.orientation()),
+                matches!(direction, Direction::Left | Direction::Up),
+            ),
+        };
+        let fits = |parent: NodeId| {
+            let parent_kind = self.container_kind(parent);
+            match zone {
+                DropZone::Center => parent_kind.is_group(),
+                DropZone::Edge(_) => {
+                    !parent_kind.is_group() && parent_kind.orientation() == kind.orientation()
+                }
+            }
+        };
+        if !target.parent(&self.tree.map).is_some_and(fits) {
+            self.nest_in_container(layout, target, kind);
+        }
+        match target.prev_sibling(&self.tree.map) {
+            _ if !before => self.move_node_after(target, node),
+            Some(prev) if prev == node => (),
+            Some(prev) => self.move_node_after(prev, node),
+            None => {
+                node.detach(&mut self.tree).insert_before(target);
+            }
+        }
+        self.select(node);
+        true
+    }
+
+    /// Moves `node` into the group `anchor` is in, right after it. If `anchor`
+    /// isn't in a group, a tabbed group is created around it first.
+    pub fn group_with(&mut self, layout: LayoutId, node: NodeId, anchor: NodeId) {
+        if node == anchor {
+            return;
+        }
+        let is_grouped = anchor
+            .parent(&self.tree.map)
+            .is_some_and(|parent| self.container_kind(parent).is_group());
+        if !is_grouped {
+            self.nest_in_container(layout, anchor, ContainerKind::Tabbed);
+        }
+        self.move_node_after(anchor, node);
+    }
+
     pub fn map(&self) -> &NodeMap {
         &self.tree.map
     }
@@ -632,7 +970,7 @@ impl LayoutTree {
     }
 
     pub fn set_container_kind(&mut self, node: NodeId, kind: ContainerKind) {
-        self.tree.data.size.set_kind(node, kind);
+        self.tree.data.size.set_kind(node, kind, &self.tree.map);
     }
 
     pub fn nest_in_container(
@@ -667,24 +1005,73 @@ impl LayoutTree {
             self.tree.data.selection.select_locally(&self.tree.map, node);
             new_parent
         };
-        self.tree.data.size.set_kind(parent, kind);
+        self.tree.data.size.set_kind(parent, kind, &self.tree.map);
         parent
     }
 
+    /// Gives every node under `node` an equal share of its parent.
+    pub fn balance(&mut self, node: NodeId) {
+        let map = &self.tree.map;
+        for container in node.traverse_preorder(map) {
+            for child in container.children(map) {
+                self.tree.data.size.set_weight(child, 1.0, map);
+            }
+        }
+    }
+
+    /// Flips the orientation of every container under and including `node`.
+    pub fn rotate(&mut self, node: NodeId) {
+        for container in node.traverse_preorder(&self.tree.map) {
+            if !container.is_empty(&self.tree.map) {
+                self.tree.data.size.rotate(container, &self.tree.map);
+            }
+        }
+    }
+
+    /// Reverses the order of children in every container under and including
+    /// `node` that has the given orientation.
+    pub fn mirror(&mut self, node: NodeId, orientation: Orientation) {
+        let containers: Vec<_> = node
+            .traverse_preorder(&self.tree.map)
+            .filter(|&n| {
+                !n.is_empty(&self.tree.map) && self.container_kind(n).orientation() == orientation
+            })
+            .collect();
+        for container in containers {
+            let children: Vec<_> = container.children_rev(&self.tree.map).collect();
+            for child in children {
+                // Reattaching under the same parent only fires a reorder
+                // event, so sizes and selection are preserved.
+                child.detach(&mut self.tree).push_back(container);
+            }
+        }
+    }
+
     pub fn swap_windows(&mut self, node_a: NodeId, node_b: NodeId) {
         self.tree.data.window.swap_windows(node_a, node_b);
+        self.tree.data.size.invalidate(node_a, &self.tree.map);
+        self.tree.data.size.invalidate(node_b, &self.tree.map);
     }
 
-    pub fn resize(&mut self, node: NodeId, screen_ratio: f64, direction: Direction) -> bool {
-        // Pick an ancestor to resize that has a sibling in the given direction.
+    /// Whether [`Self::resize`] can move the edge of `node` in the given
+    /// direction.
+    pub fn can_resize(&self, node: NodeId, direction: Direction) -> bool {
+        self.resizing_ancestor(node, direction).is_some()
+    }
+
+    /// Picks an ancestor to resize that has a sibling in the given direction.
+    fn resizing_ancestor(&self, node: NodeId, direction: Direction) -> Option<NodeId> {
         let can_resize = |&node: &NodeId| -> bool {
             let Some(parent) = node.parent(&self.tree.map) else {
                 return false;
             };
-            !self.tree.data.size.kind(parent).is_group()
-                && self.move_over(node, direction).is_some()
+            self.tree.data.size.kind(parent).is_split() && self.move_over(node, direction).is_some()
         };
-        let Some(resizing_node) = node.ancestors(&self.tree.map).filter(can_resize).next() else {
+        node.ancestors(&self.tree.map).filter(can_resize).next()
+    }
+
+    pub fn resize(&mut self, node: NodeId, screen_ratio: f64, direction: Direction) -> bool {
+        let Some(resizing_node) = self.resizing_ancestor(node, direction) else {
             return false;
         };
         let sibling = self.move_over(resizing_node, direction).unwrap();
@@ -696,7 +1083,7 @@ impl LayoutTree {
                 Some(parent)
                     if self.tree.data.size.kind(parent).orientation()
                         == direction.orientation()
-                        && !self.tree.data.size.kind(parent).is_group() =>
+                        && self.tree.data.size.kind(parent).is_split() =>
                 {
                     r * self.tree.data.size.proportion(&self.tree.map, node).unwrap()
                 }
@@ -872,6 +1259,8 @@ pub(super) enum TreeEvent {
     RemovingFromParent(NodeId),
     /// A node was removed from the forest.
     RemovedFromForest(NodeId),
+    /// A node was moved to a different position under the same parent.
+    Reordered(NodeId),
 }
 
 impl Components {
@@ -921,6 +1310,10 @@ impl tree::Observer for Components {
     fn removed_from_forest(&mut self, map: &NodeMap, node: NodeId) {
         self.dispatch_event(map, TreeEvent::RemovedFromForest(node))
     }
+
+    fn reordered(&mut self, map: &NodeMap, node: NodeId) {
+        self.dispatch_event(map, TreeEvent::Reordered(node))
+    }
 }
 
 #[cfg(test)]
@@ -1422,6 +1815,219 @@ mod tests {
         assert_frames_are(tree.calculate_layout(layout, screen, config), orig.clone());
     }
 
+    /// ┌─────┬─────┬─────┐
+    /// │     │ b1  │     │
+    /// │ a1  +─────+  a3 │
+    /// │     │ b2  │     │
+    /// └─────┴─────┴─────┘
+    fn make_transform_tree() -> (LayoutTree, LayoutId, [NodeId; 6]) {
+        let mut tree = LayoutTree::new();
+        let layout = tree.create_layout();
+        let root = tree.root(layout);
+        let a1 = tree.add_window_under(layout, root, w(1, 1));
+        let a2 = tree.add_container(root, ContainerKind::Vertical);
+        let b1 = tree.add_window_under(layout, a2, w(2, 1));
+        let b2 = tree.add_window_under(layout, a2, w(2, 2));
+        let a3 = tree.add_window_under(layout, root, w(1, 3));
+        (tree, layout, [root, a1, a2, b1, b2, a3])
+    }
+
+    #[test]
+    fn balance() {
+        let (mut tree, layout, [root, a1, a2, b1, _b2, _a3]) = make_transform_tree();
+        let screen = rect(0, 0, 3000, 3000);
+        let config = &Config::default();
+        let orig = [
+            (w(1, 1), rect(0, 0, 1000, 3000)),
+            (w(2, 1), rect(1000, 0, 1000, 1500)),
+            (w(2, 2), rect(1000, 1500, 1000, 1500)),
+            (w(1, 3), rect(2000, 0, 1000, 3000)),
+        ];
+        assert_frames_are(tree.calculate_layout(layout, screen, config), orig);
+
+        tree.resize(a1, 0.1, Direction::Right);
+        tree.resize(b1, 0.1, Direction::Down);
+        assert_frames_are(
+            tree.calculate_layout(layout, screen, config),
+            [
+                (w(1, 1), rect(0, 0, 1300, 3000)),
+                (w(2, 1), rect(1300, 0, 700, 1800)),
+                (w(2, 2), rect(1300, 1800, 700, 1200)),
+                (w(1, 3), rect(2000, 0, 1000, 3000)),
+            ],
+        );
+
+        // Only nodes under the balanced node are affected.
+        tree.balance(a2);
+        assert_frames_are(
+            tree.calculate_layout(layout, screen, config),
+            [
+                (w(1, 1), rect(0, 0, 1300, 3000)),
+                (w(2, 1), rect(1300, 0, 700, 1500)),
+                (w(2, 2), rect(1300, 1500, 700, 1500)),
+                (w(1, 3), rect(2000, 0, 1000, 3000)),
+            ],
+        );
+
+        tree.resize(b1, 0.1, Direction::Down);
+        tree.balance(root);
+        assert_frames_are(tree.calculate_layout(layout, screen, config), orig);
+    }
+
+    #[test]
+    fn rotate() {
+        let (mut tree, layout, [root, a1, a2, _b1, _b2, _a3]) = make_transform_tree();
+        let screen = rect(0, 0, 3000, 3000);
+        let config = &Config::default();
+        tree.resize(a1, 0.1, Direction::Right);
+        let orig = [
+            (w(1, 1), rect(0, 0, 1300, 3000)),
+            (w(2, 1), rect(1300, 0, 700, 1500)),
+            (w(2, 2), rect(1300, 1500, 700, 1500)),
+            (w(1, 3), rect(2000, 0, 1000, 3000)),
+        ];
+        assert_frames_are(tree.calculate_layout(layout, screen, config), orig);
+
+        tree.rotate(root);
+        assert_eq!(ContainerKind::Vertical, tree.container_kind(root));
+        assert_eq!(ContainerKind::Horizontal, tree.container_kind(a2));
+        assert_frames_are(
+            tree.calculate_layout(layout, screen, config),
+            [
+                (w(1, 1), rect(0, 0, 3000, 1300)),
+                (w(2, 1), rect(0, 1300, 1500, 700)),
+                (w(2, 2), rect(1500, 1300, 1500, 700)),
+                (w(1, 3), rect(0, 2000, 3000, 1000)),
+            ],
+        );
+
+        tree.rotate(root);
+        assert_frames_are(tree.calculate_layout(layout, screen, config), orig);
+
+        // Rotating a subtree leaves its ancestors alone.
+        tree.rotate(a2);
+        assert_eq!(ContainerKind::Horizontal, tree.container_kind(root));
+        assert_frames_are(
+            tree.calculate_layout(layout, screen, config),
+            [
+                (w(1, 1), rect(0, 0, 1300, 3000)),
+                (w(2, 1), rect(1300, 0, 350, 3000)),
+                (w(2, 2), rect(1650, 0, 350, 3000)),
+                (w(1, 3), rect(2000, 0, 1000, 3000)),
+            ],
+        );
+
+        // Groups keep their kind and remember the rotated orientation.
+        tree.set_container_kind(a2, ContainerKind::Tabbed);
+        tree.rotate(a2);
+        assert_eq!(ContainerKind::Stacked, tree.container_kind(a2));
+        assert_eq!(ContainerKind::Vertical, tree.last_ungrouped_container_kind(a2));
+    }
+
+    #[test]
+    fn mirror() {
+        let (mut tree, layout, [root, a1, a2, b1, b2, a3]) = make_transform_tree();
+        let screen = rect(0, 0, 3000, 3000);
+        let config = &Config::default();
+        tree.resize(a1, 0.1, Direction::Right);
+        tree.select(b1);
+
+        tree.mirror(root, Orientation::Horizontal);
+        tree.assert_children_are([a3, a2, a1], root);
+        tree.assert_children_are([b1, b2], a2);
+        assert_eq!(b1, tree.selection(layout));
+        assert_frames_are(
+            tree.calculate_layout(layout, screen, config),
+            [
+                (w(1, 1), rect(1700, 0, 1300, 3000)),
+                (w(2, 1), rect(1000, 0, 700, 1500)),
+                (w(2, 2), rect(1000, 1500, 700, 1500)),
+                (w(1, 3), rect(0, 0, 1000, 3000)),
+            ],
+        );
+
+        tree.mirror(root, Orientation::Vertical);
+        tree.assert_children_are([a3, a2, a1], root);
+        tree.assert_children_are([b2, b1], a2);
+        assert_eq!(b1, tree.selection(layout));
+        assert_frames_are(
+            tree.calculate_layout(layout, screen, config),
+            [
+                (w(1, 1), rect(1700, 0, 1300, 3000)),
+                (w(2, 1), rect(1000, 1500, 700, 1500)),
+                (w(2, 2), rect(1000, 0, 700, 1500)),
+                (w(1, 3), rect(0, 0, 1000, 3000)),
+            ],
+        );
+
+        // Mirroring a window does nothing.
+        tree.mirror(a1, Orientation::Horizontal);
+        tree.assert_children_are([a3, a2, a1], root);
+    }
+
+    #[test]
+    fn detach_and_restore_window() {
+        let (mut tree, layout, [root, a1, a2, b1, b2, a3]) = make_transform_tree();
+        let screen = rect(0, 0, 3000, 3000);
+        let config = &Config::default();
+        tree.resize(a1, 0.1, Direction::Right);
+        tree.resize(b1, 0.1, Direction::Down);
+        let orig = tree.calculate_layout(layout, screen, config);
+
+        let slots = tree.detach_window(w(1, 1));
+        assert_eq!(slots.len(), 1);
+        tree.assert_children_are([a2, a3], root);
+        assert_frames_are(
+            tree.calculate_layout(layout, screen, config),
+            [
+                (w(2, 1), rect(0, 0, 1235, 1800)),
+                (w(2, 2), rect(0, 1800, 1235, 1200)),
+                (w(1, 3), rect(1235, 0, 1765, 3000)),
+            ],
+        );
+
+        let a1 = tree.restore_window(w(1, 1), &slots[0]).unwrap();
+        tree.assert_children_are([a1, a2, a3], root);
+        tree.assert_children_are([b1, b2], a2);
+        assert_frames_are(tree.calculate_layout(layout, screen, config), orig);
+    }
+
+    #[test]
+    fn restore_window_falls_back_to_surviving_ancestor() {
+        let (mut tree, layout, [root, a1, _a2, _b1, b2, a3]) = make_transform_tree();
+
+        // Removing b1 culls its parent container, so b2 takes its place.
+        let slots = tree.detach_window(w(2, 1));
+        tree.assert_children_are([a1, b2, a3], root);
+
+        let b1 = tree.restore_window(w(2, 1), &slots[0]).unwrap();
+        tree.assert_children_are([a1, b1, b2, a3], root);
+        assert_eq!(tree.window_node(layout, w(2, 1)), Some(b1));
+
+        // Restoring again is a no-op.
+        assert_eq!(tree.restore_window(w(2, 1), &slots[0]), Some(b1));
+        tree.assert_children_are([a1, b1, b2, a3], root);
+    }
+
+    #[test]
+    fn restoring_in_reverse_order_undoes_detaching() {
+        let (mut tree, layout, [root, _, a2, b1, b2, _]) = make_transform_tree();
+        let screen = rect(0, 0, 3000, 3000);
+        let config = &Config::default();
+        tree.resize(a2, 0.1, Direction::Left);
+        let orig = tree.calculate_layout(layout, screen, config);
+
+        let slots_a3 = tree.detach_window(w(1, 3));
+        let slots_a1 = tree.detach_window(w(1, 1));
+        tree.assert_children_are([a2], root);
+
+        let a1 = tree.restore_window(w(1, 1), &slots_a1[0]).unwrap();
+        let a3 = tree.restore_window(w(1, 3), &slots_a3[0]).unwrap();
+        tree.assert_children_are([a1, a2, a3], root);
+        tree.assert_children_are([b1, b2], a2);
+        assert_frames_are(tree.calculate_layout(layout, screen, config), orig);
+    }
+
     #[test]
     fn set_frame_from_resize() {
         // ┌─────┬─────┬─────┐
@@ -1740,6 +2346,68 @@ mod tests {
         assert!(!tree.is_visible(tab3));
     }
 
+    #[test]
+    fn accordions_show_a_strip_of_unselected_windows() {
+        let mut tree = LayoutTree::new();
+        let layout = tree.create_layout();
+        let root = tree.root(layout);
+        tree.set_container_kind(root, ContainerKind::AccordionHorizontal);
+        let a1 = tree.add_window_under(layout, root, w(1, 1));
+        let a2 = tree.add_window_under(layout, root, w(1, 2));
+        let a3 = tree.add_window_under(layout, root, w(1, 3));
+        let screen = rect(0, 0, 300, 100);
+        let mut config = Config::default();
+        config.settings.accordion_peek = 30.0;
+
+        tree.select(a2);
+        assert_frames_are(
+            tree.calculate_layout(layout, screen, &config),
+            [
+                (w(1, 1), rect(0, 0, 30, 100)),
+                (w(1, 2), rect(30, 0, 240, 100)),
+                (w(1, 3), rect(270, 0, 30, 100)),
+            ],
+        );
+        assert!(tree.is_visible(a1) && tree.is_visible(a2) && tree.is_visible(a3));
+        assert_eq!(tree.visible_windows_under(root).len(), 3);
+
+        // Selecting a peeking window reflows the accordion around it, without
+        // surfacing anything that wasn't already visible.
+        assert_eq!(tree.select_returning_surfaced_windows(a3), [w(1, 3)]);
+        config.settings.inner_gap = 10.0;
+        assert_frames_are(
+            tree.calculate_layout(layout, screen, &config),
+            [
+                (w(1, 1), rect(0, 0, 30, 100)),
+                (w(1, 2), rect(40, 0, 30, 100)),
+                (w(1, 3), rect(80, 0, 220, 100)),
+            ],
+        );
+
+        tree.rotate(root);
+        assert_eq!(tree.container_kind(root), ContainerKind::AccordionVertical);
+        assert_frames_are(
+            tree.calculate_layout(layout, rect(0, 0, 100, 300), &config),
+            [
+                (w(1, 1), rect(0, 0, 100, 30)),
+                (w(1, 2), rect(0, 40, 100, 30)),
+                (w(1, 3), rect(0, 80, 100, 220)),
+            ],
+        );
+
+        // Windows too short to peek split the space evenly.
+        assert_frames_are(
+            tree.calculate_layout(layout, rect(0, 0, 100, 80), &config),
+            [
+                (w(1, 1), rect(0, 0, 100, 20)),
+                (w(1, 2), rect(0, 30, 100, 20)),
+                (w(1, 3), rect(0, 60, 100, 20)),
+            ],
+        );
+        // Accordions don't have weights to resize.
+        assert!(!tree.can_resize(a2, Direction::Down));
+    }
+
     #[test]
     fn is_visible_nested_groups() {
         let mut tree = LayoutTree::new();
@@ -1763,4 +2431,258 @@ mod tests {
         assert!(!tree.is_visible(tab2));
         assert!(tree.is_visible(outer_tab));
     }
+
+    #[test]
+    fn cached_frames_match_a_full_layout() {
+        let mut tree = LayoutTree::new();
+        let layout = tree.create_layout();
+        let root = tree.root(layout);
+        let a1 = tree.add_window_under(layout, root, w(1, 1));
+        let a2 = tree.add_container(root, ContainerKind::Vertical);
+        let b1 = tree.add_window_under(layout, a2, w(2, 1));
+        let b2 = tree.add_container(a2, ContainerKind::Tabbed);
+        let c1 = tree.add_window_under(layout, b2, w(3, 1));
+        let _c2 = tree.add_window_under(layout, b2, w(3, 2));
+        let a3 = tree.add_window_under(layout, root, w(1, 3));
+        let mut screen = rect(0, 0, 3000, 3000);
+        let mut config = Config::default();
+
+        let check = |tree: &LayoutTree, screen: CGRect, config: &Config| {
+            // The groups path never uses the cache.
+            let (expected, _) = tree.calculate_layout_and_groups(layout, screen, config);
+            assert_frames_are(tree.calculate_layout(layout, screen, config), expected.clone());
+            // Again, now that everything is cached.
+            assert_frames_are(tree.calculate_layout(layout, screen, config), expected);
+        };
+
+        check(&tree, screen, &config);
+        tree.resize(b1, 0.1, Direction::Down);
+        check(&tree, screen, &config);
+        tree.swap_windows(a1, c1);
+        check(&tree, screen, &config);
+        tree.mirror(root, Orientation::Horizontal);
+        check(&tree, screen, &config);
+        tree.rotate(a2);
+        check(&tree, screen, &config);
+        tree.set_fullscreen(b2, true);
+        check(&tree, screen, &config);
+        tree.set_fullscreen(b2, false);
+        tree.set_container_kind(b2, ContainerKind::Stacked);
+        check(&tree, screen, &config);
+        tree.remove_window(w(1, 3));
+        assert!(!tree.map().contains(a3));
+        check(&tree, screen, &config);
+        tree.add_window_after(layout, b1, w(2, 2));
+        check(&tree, screen, &config);
+        screen = rect(100, 0, 2000, 1000);
+        check(&tree, screen, &config);
+        config.settings.inner_gap = 10.0;
+        config.settings.outer_gap = 5.0;
+        check(&tree, screen, &config);
+        config.settings.group_bars.thickness = 30.0;
+        check(&tree, screen, &config);
+        // Accordion frames depend on the selection.
+        tree.set_container_kind(b2, ContainerKind::AccordionVertical);
+        check(&tree, screen, &config);
+        tree.select(c1);
+        check(&tree, screen, &config);
+        tree.select(a1);
+        check(&tree, screen, &config);
+    }
+
+    #[test]
+    fn unchanged_subtrees_keep_cached_frames() {
+        let mut tree = LayoutTree::new();
+        let layout = tree.create_layout();
+        let root = tree.root(layout);
+        let a1 = tree.add_container(root, ContainerKind::Vertical);
+        let b1 = tree.add_window_under(layout, a1, w(1, 1));
+        let _b2 = tree.add_window_under(layout, a1, w(1, 2));
+        let a2 = tree.add_container(root, ContainerKind::Vertical);
+        let b3 = tree.add_window_under(layout, a2, w(2, 1));
+        let _b4 = tree.add_window_under(layout, a2, w(2, 2));
+        let screen = rect(0, 0, 2000, 1000);
+        let config = &Config::default();
+        let cached = |tree: &LayoutTree, node| tree.tree.data.size.has_cached_frames(node);
+
+        let orig = tree.calculate_layout(layout, screen, config);
+        assert!(cached(&tree, root) && cached(&tree, a1) && cached(&tree, a2));
+
+        // Resizing within a2 only drops the cached frames on its path.
+        tree.resize(b3, 0.1, Direction::Down);
+        assert!(!cached(&tree, root));
+        assert!(cached(&tree, a1));
+        assert!(!cached(&tree, a2));
+
+        let frames = tree.calculate_layout(layout, screen, config);
+        assert_eq!(orig[..2], frames[..2]);
+        assert_ne!(orig[2..], frames[2..]);
+        assert!(cached(&tree, root) && cached(&tree, a1) && cached(&tree, a2));
+
+        // Selection doesn't affect frames.
+        tree.select(b1);
+        assert!(cached(&tree, root));
+    }
+
+    #[test]
+    fn largest_window_only_counts_visible_windows() {
+        let mut tree = LayoutTree::new();
+        let layout = tree.create_layout();
+        let root = tree.root(layout);
+        let a1 = tree.add_window_under(layout, root, w(1, 1));
+        let a2 = tree.add_container(root, ContainerKind::Vertical);
+        let _b1 = tree.add_window_under(layout, a2, w(2, 1));
+        let b2 = tree.add_container(a2, ContainerKind::Tabbed);
+        let c1 = tree.add_window_under(layout, b2, w(3, 1));
+        let c2 = tree.add_window_under(layout, b2, w(3, 2));
+        tree.select(c2);
+        assert_eq!(Some(a1), tree.largest_window(layout));
+
+        tree.resize(a1, -0.25, Direction::Right);
+        tree.resize(c2, 0.25, Direction::Up);
+        assert_eq!(Some(c2), tree.largest_window(layout));
+        tree.select(c1);
+        assert_eq!(Some(c1), tree.largest_window(layout));
+    }
+
+    #[test]
+    fn add_window_beside() {
+        let mut tree = LayoutTree::new();
+        let layout = tree.create_layout();
+        let root = tree.root(layout);
+        let a1 = tree.add_window_under(layout, root, w(1, 1));
+        let a2 = tree.add_window_under(layout, root, w(1, 2));
+        let screen = rect(0, 0, 300, 100);
+        let config = &Config::default();
+
+        // Splits the target in the other orientation.
+        tree.add_window_beside(layout, a2, w(1, 3), Direction::Down, 0.25);
+        let a2_parent = a2.parent(tree.map()).unwrap();
+        assert_eq!(ContainerKind::Vertical, tree.container_kind(a2_parent));
+        assert_frames_are(
+            tree.calculate_layout(layout, screen, config),
+            [
+                (w(1, 1), rect(0, 0, 150, 100)),
+                (w(1, 2), rect(150, 0, 150, 75)),
+                (w(1, 3), rect(150, 75, 150, 25)),
+            ],
+        );
+
+        // Shares the target's space when the parent is already oriented that way.
+        tree.add_window_beside(layout, a1, w(1, 4), Direction::Left, 0.5);
+        assert_eq!(a1.parent(tree.map()), Some(root));
+        assert_frames_are(
+            tree.calculate_layout(layout, screen, config),
+            [
+                (w(1, 4), rect(0, 0, 75, 100)),
+                (w(1, 1), rect(75, 0, 75, 100)),
+                (w(1, 2), rect(150, 0, 150, 75)),
+                (w(1, 3), rect(150, 75, 150, 25)),
+            ],
+        );
+    }
+
+    #[test]
+    fn add_window_beside_an_empty_container() {
+        let mut tree = LayoutTree::new();
+        let layout = tree.create_layout();
+        let root = tree.root(layout);
+        let screen = rect(0, 0, 300, 100);
+        let config = &Config::default();
+
+        let a1 = tree.add_window_beside(layout, root, w(1, 1), Direction::Right, 0.25);
+        assert_eq!(a1.parent(tree.map()), Some(root));
+        assert_frames_are(
+            tree.calculate_layout(layout, screen, config),
+            [(w(1, 1), rect(0, 0, 300, 100))],
+        );
+    }
+
+    #[test]
+    fn drop_node_on_edges() {
+        let mut tree = LayoutTree::new();
+        let layout = tree.create_layout();
+        let root = tree.root(layout);
+        let a1 = tree.add_window_under(layout, root, w(1, 1));
+        let a2 = tree.add_window_under(layout, root, w(1, 2));
+        let a3 = tree.add_window_under(layout, root, w(1, 3));
+        let screen = rect(0, 0, 3000, 1200);
+        let config = &Config::default();
+
+        // Dropping along the parent's orientation moves within it.
+        assert!(tree.drop_node(layout, a3, a1, DropZone::Edge(Direction::Left)));
+        tree.assert_children_are([a3, a1, a2], root);
+        assert_eq!(a3, tree.selection(layout));
+        assert_frames_are(
+            tree.calculate_layout(layout, screen, config),
+            [
+                (w(1, 3), rect(0, 0, 1000, 1200)),
+                (w(1, 1), rect(1000, 0, 1000, 1200)),
+                (w(1, 2), rect(2000, 0, 1000, 1200)),
+            ],
+        );
+
+        // Dropping across it splits the target.
+        assert!(tree.drop_node(layout, a3, a2, DropZone::Edge(Direction::Down)));
+        let [a1b, col] = root.children(tree.map()).collect::<Vec<_>>()[..] else {
+            panic!()
+        };
+        assert_eq!(a1, a1b);
+        tree.assert_children_are([a2, a3], col);
+        assert_eq!(ContainerKind::Vertical, tree.container_kind(col));
+        assert_frames_are(
+            tree.calculate_layout(layout, screen, config),
+            [
+                (w(1, 1), rect(0, 0, 1500, 1200)),
+                (w(1, 2), rect(1500, 0, 1500, 600)),
+                (w(1, 3), rect(1500, 600, 1500, 600)),
+            ],
+        );
+
+        // The last window leaving the root's row leaves a single column.
+        assert!(tree.drop_node(layout, a1, a3, DropZone::Edge(Direction::Up)));
+        tree.assert_children_are([col], root);
+        tree.assert_children_are([a2, a1, a3], col);
+        assert_frames_are(
+            tree.calculate_layout(layout, screen, config),
+            [
+                (w(1, 2), rect(0, 0, 3000, 400)),
+                (w(1, 1), rect(0, 400, 3000, 400)),
+                (w(1, 3), rect(0, 800, 3000, 400)),
+            ],
+        );
+
+        // Dropping next to where the node already is leaves it there.
+        assert!(tree.drop_node(layout, a2, a1, DropZone::Edge(Direction::Up)));
+        tree.assert_children_are([a2, a1, a3], col);
+        assert!(!tree.drop_node(layout, a2, a2, DropZone::Edge(Direction::Right)));
+    }
+
+    #[test]
+    fn drop_node_in_center() {
+        let mut tree = LayoutTree::new();
+        let layout = tree.create_layout();
+        let root = tree.root(layout);
+        let a1 = tree.add_window_under(layout, root, w(1, 1));
+        let a2 = tree.add_window_under(layout, root, w(1, 2));
+        let a3 = tree.add_window_under(layout, root, w(1, 3));
+
+        // Dropping on a window outside a group creates a tabbed group.
+        assert!(tree.drop_node(layout, a3, a1, DropZone::Center));
+        let [group, a2b] = root.children(tree.map()).collect::<Vec<_>>()[..] else {
+            panic!()
+        };
+        assert_eq!(a2, a2b);
+        assert_eq!(ContainerKind::Tabbed, tree.container_kind(group));
+        tree.assert_children_are([a1, a3], group);
+        assert_eq!(a3, tree.selection(layout));
+
+        // Dropping on a window in a group joins it.
+        tree.set_container_kind(group, ContainerKind::Stacked);
+        assert!(tree.drop_node(layout, a2, a1, DropZone::Center));
+        tree.assert_children_are([group], root);
+        tree.assert_children_are([a1, a2, a3], group);
+        assert_eq!(ContainerKind::Stacked, tree.container_kind(group));
+        assert_eq!(a2, tree.selection(layout));
+    }
 }
@@ -8,7 +8,7 @@ use std::time::Instant;
 use objc2_core_foundation::CGRect;
 
 use super::spring::SpringAnimation;
-use crate::config::CenterMode;
+use crate::config::{CenterMode, ScrollAnimation};
 
 #[derive(Debug, Clone)]
 pub enum ScrollState {
@@ -32,13 +32,57 @@ impl ScrollState {
     }
 }
 
+/// Turns scroll deltas into whole steps, carrying the remainder over to the
+/// next event.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScrollAccumulator {
+    progress: f64,
+}
+
+impl ScrollAccumulator {
+    /// Adds the delta of a scroll event, scaled by `sensitivity`, and returns
+    /// the number of whole steps of size `step` scrolled so far.
+    ///
+    /// Mouse wheels report small whole numbers of lines, so each of their
+    /// events counts as a full step regardless of sensitivity.
+    pub fn accumulate_event(&mut self, delta: f64, sensitivity: f64, step: f64) -> Option<i32> {
+        let scaled = delta * sensitivity;
+        let is_discrete = delta.abs() < 10.0 && delta.fract() == 0.0;
+        let delta = if is_discrete {
+            scaled.signum() * step
+        } else {
+            scaled
+        };
+        self.accumulate(delta, step)
+    }
+
+    pub fn accumulate(&mut self, delta: f64, step: f64) -> Option<i32> {
+        if step <= 0.0 {
+            return None;
+        }
+        self.progress += delta;
+        let steps = (self.progress / step).trunc() as i32;
+        if steps != 0 {
+            self.progress -= steps as f64 * step;
+            Some(steps)
+        } else {
+            None
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.progress = 0.0;
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ViewportState {
     pub scroll: ScrollState,
     pub active_column_index: usize,
     pub screen_width: f64,
     pub user_scrolling: bool,
-    pub scroll_progress: f64,
+    pub scroll_progress: ScrollAccumulator,
+    pub animation: ScrollAnimation,
 }
 
 impl ViewportState {
@@ -48,7 +92,8 @@ impl ViewportState {
             active_column_index: 0,
             screen_width,
             user_scrolling: false,
-            scroll_progress: 0.0,
+            scroll_progress: ScrollAccumulator::default(),
+            animation: ScrollAnimation::default(),
         }
     }
 
@@ -115,32 +160,33 @@ impl ViewportState {
         self.scroll = ScrollState::Static(offset);
     }
 
+    pub fn set_animation(&mut self, animation: ScrollAnimation) {
+        self.animation = animation;
+    }
+
     pub fn animate_to(&mut self, target: f64, now: Instant) {
+        if self.animation.instant {
+            self.scroll = ScrollState::Static(target);
+            return;
+        }
         match &mut self.scroll {
             ScrollState::Animating(spring) => {
                 spring.retarget(target, now);
             }
             ScrollState::Static(current) => {
-                self.scroll =
-                    ScrollState::Animating(SpringAnimation::with_defaults(*current, target, now));
+                let ScrollAnimation { response, damping_fraction, .. } = self.animation;
+                self.scroll = ScrollState::Animating(SpringAnimation::new(
+                    *current,
+                    target,
+                    0.0,
+                    response,
+                    damping_fraction,
+                    now,
+                ));
             }
         }
     }
 
-    pub fn accumulate_scroll(&mut self, delta: f64, avg_column_width: f64) -> Option<i32> {
-        if avg_column_width <= 0.0 {
-            return None;
-        }
-        self.scroll_progress += delta;
-        let steps = (self.scroll_progress / avg_column_width).trunc() as i32;
-        if steps != 0 {
-            self.scroll_progress -= steps as f64 * avg_column_width;
-            Some(steps)
-        } else {
-            None
-        }
-    }
-
     pub fn is_animating(&self, now: Instant) -> bool {
         match &self.scroll {
             ScrollState::Static(_) => false,
@@ -226,6 +272,29 @@ mod tests {
         CGRect::new(CGPoint::new(x, y), CGSize::new(w, h))
     }
 
+    #[test]
+    fn scroll_accumulator_carries_remainder() {
+        let mut acc = ScrollAccumulator::default();
+        assert_eq!(acc.accumulate(60.0, 100.0), None);
+        assert_eq!(acc.accumulate(60.0, 100.0), Some(1));
+        assert_eq!(acc.accumulate(-30.0, 100.0), None);
+        assert_eq!(acc.accumulate(-200.0, 100.0), Some(-2));
+        assert_eq!(acc.accumulate(10.0, 0.0), None);
+        acc.reset();
+        assert_eq!(acc.accumulate(90.0, 100.0), None);
+    }
+
+    #[test]
+    fn scroll_accumulator_counts_wheel_notches_as_steps() {
+        let mut acc = ScrollAccumulator::default();
+        // Small whole-number deltas are mouse wheel notches.
+        assert_eq!(acc.accumulate_event(1.0, 0.1, 100.0), Some(1));
+        assert_eq!(acc.accumulate_event(-2.0, 0.1, 100.0), Some(-1));
+        // Anything else is scaled by the sensitivity.
+        assert_eq!(acc.accumulate_event(0.5, 100.0, 100.0), None);
+        assert_eq!(acc.accumulate_event(20.0, 4.0, 100.0), Some(1));
+    }
+
     #[test]
     fn ensure_column_visible_already_visible() {
         let now = Instant::now();
@@ -294,6 +363,55 @@ mod tests {
         assert!(!vp.is_animating(Instant::now()));
     }
 
+    #[test]
+    fn instant_animation_jumps_to_target() {
+        let now = Instant::now();
+        let mut vp = ViewportState::new(1000.0);
+        vp.set_animation(ScrollAnimation {
+            instant: true,
+            ..ScrollAnimation::default()
+        });
+        vp.animate_to(10.0, now);
+        assert!(!vp.is_animating(now));
+        assert_eq!(vp.scroll_offset(now), 10.0);
+    }
+
+    #[test]
+    fn spring_parameters_are_used() {
+        let now = Instant::now();
+        let mut fast = ViewportState::new(1000.0);
+        fast.set_animation(ScrollAnimation {
+            instant: false,
+            response: 0.1,
+            damping_fraction: 1.0,
+        });
+        let mut slow = ViewportState::new(1000.0);
+        slow.set_animation(ScrollAnimation {
+            instant: false,
+            response: 1.0,
+            damping_fraction: 1.0,
+        });
+        fast.animate_to(1000.0, now);
+        slow.animate_to(1000.0, now);
+        let later = now + std::time::Duration::from_millis(300);
+        assert!(!fast.is_animating(later));
+        assert!(slow.is_animating(later));
+        assert!(slow.scroll_offset(later) < fast.scroll_offset(later));
+    }
+
+    #[test]
+    fn retargeting_keeps_velocity() {
+        let now = Instant::now();
+        let mut vp = ViewportState::new(1000.0);
+        vp.animate_to(1000.0, now);
+        let mid = now + std::time::Duration::from_millis(100);
+        let before = vp.scroll_offset(mid);
+        vp.animate_to(0.0, mid);
+        // The viewport keeps moving toward the old target for a moment.
+        let after = vp.scroll_offset(mid + std::time::Duration::from_millis(10));
+        assert!(after > before, "{before} {after}");
+    }
+
     #[test]
     fn completed_animation_settles_to_static() {
         let now = Instant::now();
@@ -85,6 +85,7 @@ impl Selection {
         match event {
             AddedToForest(_node) => {}
             AddedToParent(_node) => {}
+            Reordered(_node) => {}
             Copied { src, dest, .. } => {
                 let Some(info) = self.nodes.get(src) else {
                     return;
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 use core::fmt::Debug;
+use std::cell::RefCell;
 use std::mem;
 
 use objc2_core_foundation::{CGPoint, CGRect, CGSize};
@@ -11,12 +12,19 @@ use super::layout_tree::TreeEvent;
 use super::selection::Selection;
 use super::tree::{NodeId, NodeMap};
 use crate::actor::app::WindowId;
-use crate::config::Config;
+use crate::config::{AspectRatio, Config, HorizontalPlacement, VerticalPlacement};
 use crate::sys::geometry::{CGRectExt, Round};
 
 #[derive(Default, Serialize, Deserialize)]
 pub struct Size {
     info: slotmap::SecondaryMap<NodeId, LayoutInfo>,
+    /// Frames of container subtrees from previous calls to `get_sizes`.
+    ///
+    /// The entries for a node and all of its ancestors are dropped whenever
+    /// anything under the node changes, so any entry still here can be
+    /// reused as long as its inputs match.
+    #[serde(skip)]
+    cache: RefCell<slotmap::SecondaryMap<NodeId, CachedFrames>>,
 }
 
 #[allow(unused)]
@@ -28,6 +36,11 @@ pub enum ContainerKind {
     Vertical,
     Tabbed,
     Stacked,
+    /// Gives most of the space to the selected child and a strip of the
+    /// configured width to each of the others, side by side.
+    AccordionHorizontal,
+    /// Like [`ContainerKind::AccordionHorizontal`], but top to bottom.
+    AccordionVertical,
 }
 
 impl ContainerKind {
@@ -44,6 +57,13 @@ impl ContainerKind {
             Orientation::Vertical => ContainerKind::Stacked,
         }
     }
+
+    pub fn accordion(orientation: Orientation) -> Self {
+        match orientation {
+            Orientation::Horizontal => ContainerKind::AccordionHorizontal,
+            Orientation::Vertical => ContainerKind::AccordionVertical,
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -57,11 +77,16 @@ impl ContainerKind {
     pub fn orientation(self) -> Orientation {
         use ContainerKind::*;
         match self {
-            Horizontal | Tabbed => Orientation::Horizontal,
-            Vertical | Stacked => Orientation::Vertical,
+            Horizontal | Tabbed | AccordionHorizontal => Orientation::Horizontal,
+            Vertical | Stacked | AccordionVertical => Orientation::Vertical,
         }
     }
 
+    /// Whether the children divide the container between them by weight.
+    pub fn is_split(self) -> bool {
+        matches!(self, ContainerKind::Horizontal | ContainerKind::Vertical)
+    }
+
     pub fn is_group(self) -> bool {
         use ContainerKind::*;
         match self {
@@ -69,6 +94,19 @@ impl ContainerKind {
             _ => false,
         }
     }
+
+    /// Returns the same kind of container in the opposite orientation.
+    pub fn rotated(self) -> Self {
+        use ContainerKind::*;
+        match self {
+            Horizontal => Vertical,
+            Vertical => Horizontal,
+            Tabbed => Stacked,
+            Stacked => Tabbed,
+            AccordionHorizontal => AccordionVertical,
+            AccordionVertical => AccordionHorizontal,
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -88,6 +126,16 @@ impl Direction {
             Up | Down => Orientation::Vertical,
         }
     }
+
+    pub(super) fn opposite(self) -> Direction {
+        use Direction::*;
+        match self {
+            Left => Right,
+            Right => Left,
+            Up => Down,
+            Down => Up,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -152,6 +200,49 @@ struct LayoutInfo {
     is_fullscreen: bool,
 }
 
+/// The frames of every window under a container.
+struct CachedFrames {
+    key: CacheKey,
+    frames: Vec<(WindowId, CGRect)>,
+    /// The selected child of each accordion under the container, which the
+    /// frames also depend on.
+    accordions: Vec<(NodeId, Option<NodeId>)>,
+}
+
+/// The inputs from outside a subtree that its frames depend on.
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct CacheKey {
+    rect: CGRect,
+    screen: CGRect,
+    is_scroll: bool,
+    outer_gap: f64,
+    inner_gap: f64,
+    accordion_peek: f64,
+    group_bars: Option<(f64, HorizontalPlacement, VerticalPlacement)>,
+    aspect_ratio: Option<AspectRatio>,
+}
+
+impl CacheKey {
+    fn new(config: &Config, screen: CGRect, is_scroll: bool) -> Self {
+        let settings = &config.settings;
+        let group_bars = &settings.group_bars;
+        CacheKey {
+            rect: CGRect::ZERO,
+            screen,
+            is_scroll,
+            outer_gap: settings.outer_gap,
+            inner_gap: settings.inner_gap,
+            accordion_peek: settings.accordion_peek,
+            group_bars: group_bars.enable.then_some((
+                group_bars.thickness,
+                group_bars.horizontal_placement,
+                group_bars.vertical_placement,
+            )),
+            aspect_ratio: settings.experimental.scroll.aspect_ratio(),
+        }
+    }
+}
+
 impl Size {
     pub(super) fn handle_event(&mut self, map: &NodeMap, event: TreeEvent) {
         match event {
@@ -162,29 +253,54 @@ impl Size {
                 let parent = node.parent(map).unwrap();
                 self.info[node].size = 1.0;
                 self.info[parent].total += 1.0;
+                self.invalidate(parent, map);
             }
             TreeEvent::Copied { src, dest, .. } => {
                 self.info.insert(dest, self.info[src].clone());
             }
             TreeEvent::RemovingFromParent(node) => {
-                self.info[node.parent(map).unwrap()].total -= self.info[node].size;
+                let parent = node.parent(map).unwrap();
+                self.info[parent].total -= self.info[node].size;
+                self.invalidate(parent, map);
             }
             TreeEvent::RemovedFromForest(node) => {
                 self.info.remove(node);
+                self.cache.get_mut().remove(node);
+            }
+            TreeEvent::Reordered(node) => {
+                self.invalidate(node, map);
             }
         }
     }
 
+    /// Drops the cached frames of `node` and its ancestors.
+    ///
+    /// This must be called for changes to the tree that the size component
+    /// doesn't otherwise see, like swapping the windows of two nodes.
+    pub(super) fn invalidate(&mut self, node: NodeId, map: &NodeMap) {
+        let cache = self.cache.get_mut();
+        for node in node.ancestors(map) {
+            cache.remove(node);
+        }
+    }
+
+    #[cfg(test)]
+    pub(super) fn has_cached_frames(&self, node: NodeId) -> bool {
+        self.cache.borrow().contains_key(node)
+    }
+
     pub(super) fn assume_size_of(&mut self, new: NodeId, old: NodeId, map: &NodeMap) {
         assert_eq!(new.parent(map), old.parent(map));
         let parent = new.parent(map).unwrap();
         self.info[parent].total -= self.info[new].size;
         self.info[new].size = mem::replace(&mut self.info[old].size, 0.0);
+        self.invalidate(parent, map);
     }
 
-    pub(super) fn set_kind(&mut self, node: NodeId, kind: ContainerKind) {
+    pub(super) fn set_kind(&mut self, node: NodeId, kind: ContainerKind, map: &NodeMap) {
+        self.invalidate(node, map);
         self.info[node].kind = kind;
-        if !kind.is_group() {
+        if kind.is_split() {
             self.info[node].last_ungrouped_kind = kind;
         }
     }
@@ -197,6 +313,13 @@ impl Size {
         self.info[node].last_ungrouped_kind
     }
 
+    pub(super) fn rotate(&mut self, node: NodeId, map: &NodeMap) {
+        self.invalidate(node, map);
+        let info = &mut self.info[node];
+        info.kind = info.kind.rotated();
+        info.last_ungrouped_kind = info.last_ungrouped_kind.rotated();
+    }
+
     pub(super) fn proportion(&self, map: &NodeMap, node: NodeId) -> Option<f64> {
         let Some(parent) = node.parent(map) else { return None };
         Some(f64::from(self.info[node].size) / f64::from(self.info[parent].total))
@@ -216,6 +339,7 @@ impl Size {
         let share = share.max(-self.info[node].size);
         self.info[from].size -= share;
         self.info[node].size += share;
+        self.invalidate(node, map);
     }
 
     pub(super) fn set_weight(&mut self, node: NodeId, weight: f32, map: &NodeMap) {
@@ -224,9 +348,11 @@ impl Size {
         if let Some(parent) = node.parent(map) {
             self.info[parent].total += weight - old;
         }
+        self.invalidate(node, map);
     }
 
-    pub(super) fn set_fullscreen(&mut self, node: NodeId, is_fullscreen: bool) {
+    pub(super) fn set_fullscreen(&mut self, node: NodeId, is_fullscreen: bool, map: &NodeMap) {
+        self.invalidate(node, map);
         self.info[node].is_fullscreen = is_fullscreen;
     }
 
@@ -273,6 +399,8 @@ impl Size {
             is_scroll,
             sizes: &mut sizes,
             groups: None,
+            cache_key: Some(CacheKey::new(config, screen, is_scroll)),
+            accordions: vec![],
         }
         .visit(root, screen);
         sizes
@@ -305,6 +433,9 @@ impl Size {
             is_scroll,
             sizes: &mut sizes,
             groups: Some(&mut groups),
+            // Group visibility depends on the selection, which we don't track.
+            cache_key: None,
+            accordions: vec![],
         }
         .visit(root, screen);
         (sizes, groups)
@@ -322,6 +453,10 @@ struct Visitor<'a, 'out> {
     is_scroll: bool,
     sizes: &'out mut Vec<(WindowId, CGRect)>,
     groups: Option<&'out mut Vec<GroupBarInfo>>,
+    /// Set when cached frames can be used, with everything but the rect.
+    cache_key: Option<CacheKey>,
+    /// The selected child of each accordion visited so far.
+    accordions: Vec<(NodeId, Option<NodeId>)>,
 }
 
 impl<'a, 'out> Visitor<'a, 'out> {
@@ -357,6 +492,40 @@ impl<'a, 'out> Visitor<'a, 'out> {
             return;
         }
 
+        let cache_key = self.cache_key.map(|key| CacheKey { rect, ..key });
+        if let Some(key) = cache_key
+            && let Some(cached) = self.size.cache.borrow().get(node)
+            && cached.key == key
+            && cached.accordions.iter().all(|&(accordion, selected)| {
+                self.selection.last_selection(self.map, accordion) == selected
+            })
+        {
+            self.sizes.extend_from_slice(&cached.frames);
+            self.accordions.extend_from_slice(&cached.accordions);
+            return;
+        }
+        let start = self.sizes.len();
+        let accordions_start = self.accordions.len();
+        self.visit_children(node, rect, is_in_visibility_path, is_parent_visible, is_selected);
+        if let Some(key) = cache_key {
+            let frames = self.sizes[start..].to_vec();
+            let accordions = self.accordions[accordions_start..].to_vec();
+            self.size
+                .cache
+                .borrow_mut()
+                .insert(node, CachedFrames { key, frames, accordions });
+        }
+    }
+
+    fn visit_children(
+        &mut self,
+        node: NodeId,
+        rect: CGRect,
+        is_in_visibility_path: bool,
+        is_parent_visible: bool,
+        is_selected: bool,
+    ) {
+        let info = &self.size.info[node];
         use ContainerKind::*;
         match info.kind {
             Tabbed | Stacked => {
@@ -507,10 +676,86 @@ impl<'a, 'out> Visitor<'a, 'out> {
                     y = rect.max().y + inner_gap;
                 }
             }
+            AccordionHorizontal | AccordionVertical => {
+                let local_selection = self.selection.local_selection(self.map, node);
+                let selected_child = self.selection.last_selection(self.map, node);
+                self.accordions.push((node, selected_child));
+                let children: Vec<NodeId> = node.children(self.map).collect();
+                let selected_index =
+                    children.iter().position(|&child| Some(child) == selected_child);
+                let frames = accordion_frames(
+                    rect,
+                    info.kind.orientation(),
+                    children.len(),
+                    selected_index.unwrap_or(0),
+                    self.config.settings.accordion_peek,
+                    self.config.settings.inner_gap,
+                );
+                for (child, frame) in children.into_iter().zip(frames) {
+                    self.visit_node(
+                        child,
+                        frame,
+                        is_in_visibility_path,
+                        is_parent_visible,
+                        is_selected && local_selection == Some(child),
+                    );
+                }
+            }
         }
     }
 }
 
+/// Divides `rect` between the `count` children of an accordion.
+///
+/// Every child but the selected one gets a strip `peek` points long, and the
+/// selected child gets the rest. The strip a child peeks out from is always
+/// inside the frame it gets when selected, so the mouse stays over it.
+fn accordion_frames(
+    rect: CGRect,
+    orientation: Orientation,
+    count: usize,
+    selected: usize,
+    peek: f64,
+    inner_gap: f64,
+) -> Vec<CGRect> {
+    if count == 0 {
+        return vec![];
+    }
+    let (mut start, length) = match orientation {
+        Orientation::Horizontal => (rect.origin.x, rect.size.width),
+        Orientation::Vertical => (rect.origin.y, rect.size.height),
+    };
+    let available = (length - inner_gap * (count - 1) as f64).max(0.0);
+    // Never leave the selected child less room than the others.
+    let peek = peek.min(available / count as f64).max(0.0);
+    let selected_length = available - peek * (count - 1) as f64;
+    (0..count)
+        .map(|index| {
+            let length = if index == selected {
+                selected_length
+            } else {
+                peek
+            };
+            let frame = match orientation {
+                Orientation::Horizontal => CGRect::new(
+                    CGPoint::new(start, rect.origin.y),
+                    CGSize::new(length, rect.size.height),
+                ),
+                Orientation::Vertical => CGRect::new(
+                    CGPoint::new(rect.origin.x, start),
+                    CGSize::new(rect.size.width, length),
+                ),
+            }
+            .round();
+            start = match orientation {
+                Orientation::Horizontal => frame.max().x,
+                Orientation::Vertical => frame.max().y,
+            } + inner_gap;
+            frame
+        })
+        .collect()
+}
+
 /// Calculate frames for group and indicator, reserving space for the indicator
 fn size_with_group_indicator(
     rect: CGRect,
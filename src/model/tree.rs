@@ -53,6 +53,11 @@ impl NodeMap {
     pub fn reserve(&mut self, additional: usize) {
         self.map.reserve(additional)
     }
+
+    /// Returns whether the node is still in the map.
+    pub fn contains(&self, node: NodeId) -> bool {
+        self.map.contains_key(node)
+    }
 }
 
 impl Index<NodeId> for NodeMap {
@@ -259,6 +264,9 @@ where
     fn removing_from_parent(&mut self, map: &NodeMap, node: NodeId);
     fn removed_child(tree: &mut Tree<Self>, parent: NodeId);
     fn removed_from_forest(&mut self, map: &NodeMap, node: NodeId);
+    /// Called when a node is moved to a different position under the same
+    /// parent.
+    fn reordered(&mut self, map: &NodeMap, node: NodeId);
 }
 
 impl Observer for () {
@@ -267,6 +275,7 @@ impl Observer for () {
     fn removing_from_parent(&mut self, _forest: &NodeMap, _node: NodeId) {}
     fn removed_child(_tree: &mut Tree<Self>, _parent: NodeId) {}
     fn removed_from_forest(&mut self, _forest: &NodeMap, _node: NodeId) {}
+    fn reordered(&mut self, _forest: &NodeMap, _node: NodeId) {}
 }
 
 #[must_use = "Unattached nodes should be inserted into the tree or created as a root with OwnedNode"]
@@ -377,6 +386,8 @@ impl<'a, O: Observer> DetachedNode<'a, O> {
         attach(&mut self);
         if old_parent != new_parent {
             self.tree.data.added_to_parent(&self.tree.map, self.id);
+        } else {
+            self.tree.data.reordered(&self.tree.map, self.id);
         }
         ReattachedNode {
             detached: self,
@@ -744,6 +755,7 @@ mod tests {
         RemovingFromParent(NodeId, NodeId),
         RemovedChild(NodeId),
         RemovedFromForest(NodeId),
+        Reordered(NodeId),
     }
     use TreeEvent::*;
 
@@ -765,6 +777,9 @@ mod tests {
         fn removed_from_forest(&mut self, _map: &NodeMap, node: NodeId) {
             self.0.push(RemovedFromForest(node))
         }
+        fn reordered(&mut self, _map: &NodeMap, node: NodeId) {
+            self.0.push(Reordered(node))
+        }
     }
 
     #[test]
@@ -972,11 +987,11 @@ mod tests {
         t.child1.detach(&mut t.tree).insert_after(t.child2);
         t.assert_children_are([t.child2, t.child1, t.child3], t.root);
         t.assert_children_are([t.gc1], t.child2);
-        t.assert_events_are([]);
+        t.assert_events_are([Reordered(t.child1)]);
 
         t.child1.detach(&mut t.tree).insert_before(t.child2);
         t.assert_children_are([t.child1, t.child2, t.child3], t.root);
-        t.assert_events_are([]);
+        t.assert_events_are([Reordered(t.child1)]);
 
         t.child2.detach(&mut t.tree).push_back(t.child1).with(|_id, tree| {
             TestTree::assert_events_are_inner(
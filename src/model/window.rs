@@ -121,6 +121,7 @@ impl Window {
                 }
             }
             RemovingFromParent(_) => (),
+            Reordered(_) => (),
             RemovedFromForest(node) => {
                 if let Some(wid) = self.windows.remove(node) {
                     if let Some(window_nodes) = self.window_nodes.get_mut(&wid) {
@@ -0,0 +1,445 @@
+// Copyright The Glide Authors
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Animation of window frames.
+//!
+//! Nothing here sleeps or reads the clock. The caller decides when to advance
+//! the animations, and what to do with the frames they produce.
+
+use std::time::{Duration, Instant};
+
+use objc2_core_foundation::{CGPoint, CGRect, CGSize};
+
+use super::spring::SpringAnimation;
+use crate::actor::app::WindowId;
+use crate::collections::BTreeMap;
+use crate::config::{AnimationConfig, Easing};
+
+/// What to do with a window on one tick of its animation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FrameStep {
+    /// Move the window, keeping its size.
+    ///
+    /// Resizing windows is too slow to do on every frame, so we only move them
+    /// until they are close to their new size.
+    Move(CGPoint),
+    /// Move the window and give it its final size. This happens once, about
+    /// halfway through the animation.
+    Resize(CGRect),
+    /// The animation is over and the window should be set to its final frame.
+    Finish(CGRect),
+}
+
+/// The frame animations of all windows that are currently moving.
+#[derive(Debug)]
+pub struct WindowAnimations {
+    windows: BTreeMap<WindowId, FrameAnimation>,
+    easing: Easing,
+    duration: Duration,
+}
+
+#[derive(Debug)]
+struct FrameAnimation {
+    x: Motion,
+    y: Motion,
+    width: Motion,
+    height: Motion,
+    /// The size at the time the animation was started or retargeted.
+    size_from: CGSize,
+    size_written: bool,
+}
+
+/// The animation of a single value.
+#[derive(Debug)]
+enum Motion {
+    Spring(SpringAnimation),
+    Curve {
+        from: f64,
+        to: f64,
+        start: Instant,
+        duration: Duration,
+        easing: Easing,
+    },
+}
+
+impl WindowAnimations {
+    pub fn new(config: &AnimationConfig) -> Self {
+        let mut this = WindowAnimations {
+            windows: BTreeMap::new(),
+            easing: Easing::Linear,
+            duration: Duration::ZERO,
+        };
+        this.set_config(config);
+        this
+    }
+
+    /// Changes how new animations move. Windows that are already animating
+    /// keep their current curve.
+    pub fn set_config(&mut self, config: &AnimationConfig) {
+        let config = config.clone().validated();
+        self.easing = config.easing;
+        self.duration = Duration::from_secs_f64(config.duration);
+    }
+
+    /// Animates a window from `from` to `to`.
+    ///
+    /// If the window is already animating, it is retargeted from its current
+    /// position and velocity and `from` is ignored. Returns true if a new
+    /// animation was started.
+    pub fn animate(&mut self, wid: WindowId, from: CGRect, to: CGRect, now: Instant) -> bool {
+        if let Some(anim) = self.windows.get_mut(&wid) {
+            anim.retarget(to, now);
+            return false;
+        }
+        let motion = |from, to| Motion::new(from, to, self.easing, self.duration, now);
+        let anim = FrameAnimation {
+            x: motion(from.origin.x, to.origin.x),
+            y: motion(from.origin.y, to.origin.y),
+            width: motion(from.size.width, to.size.width),
+            height: motion(from.size.height, to.size.height),
+            size_from: from.size,
+            size_written: from.size == to.size,
+        };
+        self.windows.insert(wid, anim);
+        true
+    }
+
+    /// Stops animating a window. Returns true if it was animating.
+    pub fn cancel(&mut self, wid: WindowId) -> bool {
+        self.windows.remove(&wid).is_some()
+    }
+
+    pub fn is_animating(&self, wid: WindowId) -> bool {
+        self.windows.contains_key(&wid)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.windows.is_empty()
+    }
+
+    /// The frame an animating window should have at `now`.
+    pub fn current_frame(&self, wid: WindowId, now: Instant) -> Option<CGRect> {
+        Some(self.windows.get(&wid)?.frame_at(now))
+    }
+
+    /// Advances every animation to `now`, returning what should happen to each
+    /// window. Finished animations are removed.
+    pub fn tick(&mut self, now: Instant) -> Vec<(WindowId, FrameStep)> {
+        let mut steps = Vec::with_capacity(self.windows.len());
+        self.windows.retain(|&wid, anim| {
+            let step = anim.step(now);
+            steps.push((wid, step));
+            !matches!(step, FrameStep::Finish(_))
+        });
+        steps
+    }
+}
+
+impl FrameAnimation {
+    fn retarget(&mut self, to: CGRect, now: Instant) {
+        let old_target_size = self.target().size;
+        self.x.retarget(to.origin.x, now);
+        self.y.retarget(to.origin.y, now);
+        self.width.retarget(to.size.width, now);
+        self.height.retarget(to.size.height, now);
+        if to.size != old_target_size {
+            self.size_from = self.frame_at(now).size;
+            self.size_written = false;
+        }
+    }
+
+    fn target(&self) -> CGRect {
+        CGRect::new(
+            CGPoint::new(self.x.target(), self.y.target()),
+            CGSize::new(self.width.target(), self.height.target()),
+        )
+    }
+
+    fn frame_at(&self, now: Instant) -> CGRect {
+        CGRect::new(
+            CGPoint::new(self.x.value_at(now), self.y.value_at(now)),
+            CGSize::new(self.width.value_at(now), self.height.value_at(now)),
+        )
+    }
+
+    fn is_complete(&self, now: Instant) -> bool {
+        [&self.x, &self.y, &self.width, &self.height]
+            .into_iter()
+            .all(|motion| motion.is_complete(now))
+    }
+
+    fn step(&mut self, now: Instant) -> FrameStep {
+        let target = self.target();
+        if self.is_complete(now) {
+            return FrameStep::Finish(target);
+        }
+        let frame = self.frame_at(now);
+        if !self.size_written && self.size_progress(frame.size) >= 0.5 {
+            self.size_written = true;
+            return FrameStep::Resize(CGRect::new(frame.origin, target.size));
+        }
+        FrameStep::Move(frame.origin)
+    }
+
+    /// How far the size has moved toward its target since the animation was
+    /// started, from 0 to 1.
+    fn size_progress(&self, size: CGSize) -> f64 {
+        let target = self.target().size;
+        let total = (target.width - self.size_from.width)
+            .abs()
+            .max((target.height - self.size_from.height).abs());
+        let remaining = (target.width - size.width).abs().max((target.height - size.height).abs());
+        if total == 0.0 {
+            1.0
+        } else {
+            1.0 - remaining / total
+        }
+    }
+}
+
+impl Motion {
+    fn new(from: f64, to: f64, easing: Easing, duration: Duration, start: Instant) -> Self {
+        match easing {
+            Easing::Spring { response, damping_fraction } => Motion::Spring(SpringAnimation::new(
+                from,
+                to,
+                0.0,
+                response,
+                damping_fraction,
+                start,
+            )),
+            Easing::Linear | Easing::EaseOutCubic => Motion::Curve {
+                from,
+                to,
+                start,
+                duration,
+                easing,
+            },
+        }
+    }
+
+    /// Changes the target, starting from the current value. Springs also keep
+    /// their current velocity.
+    fn retarget(&mut self, new_target: f64, now: Instant) {
+        match self {
+            Motion::Spring(spring) => spring.retarget(new_target, now),
+            Motion::Curve { .. } => {
+                let current = self.value_at(now);
+                let Motion::Curve { from, to, start, .. } = self else {
+                    unreachable!()
+                };
+                *from = current;
+                *to = new_target;
+                *start = now;
+            }
+        }
+    }
+
+    fn target(&self) -> f64 {
+        match self {
+            Motion::Spring(spring) => spring.target(),
+            Motion::Curve { to, .. } => *to,
+        }
+    }
+
+    fn value_at(&self, now: Instant) -> f64 {
+        match *self {
+            Motion::Spring(ref spring) => spring.value_at(now),
+            Motion::Curve {
+                from,
+                to,
+                start,
+                duration,
+                easing,
+            } => {
+                let t = if duration.is_zero() {
+                    1.0
+                } else {
+                    (now.saturating_duration_since(start).as_secs_f64() / duration.as_secs_f64())
+                        .min(1.0)
+                };
+                let s = match easing {
+                    Easing::EaseOutCubic => 1.0 - (1.0 - t).powi(3),
+                    _ => t,
+                };
+                from + (to - from) * s
+            }
+        }
+    }
+
+    fn is_complete(&self, now: Instant) -> bool {
+        match *self {
+            Motion::Spring(ref spring) => spring.is_complete(now),
+            Motion::Curve { start, duration, .. } => {
+                now.saturating_duration_since(start) >= duration
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn rect(x: i32, y: i32, w: i32, h: i32) -> CGRect {
+        CGRect::new(CGPoint::new(x.into(), y.into()), CGSize::new(w.into(), h.into()))
+    }
+
+    fn w(idx: u32) -> WindowId {
+        WindowId::new(1, idx)
+    }
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    fn animations(easing: Easing) -> WindowAnimations {
+        WindowAnimations::new(&AnimationConfig {
+            duration: 0.3,
+            easing,
+            disabled_apps: vec![],
+        })
+    }
+
+    fn spring() -> WindowAnimations {
+        animations(Easing::Spring {
+            response: 0.25,
+            damping_fraction: 1.0,
+        })
+    }
+
+    #[test]
+    fn moves_then_resizes_then_finishes() {
+        let start = Instant::now();
+        let mut anims = spring();
+        assert!(anims.animate(w(1), rect(0, 0, 100, 100), rect(500, 0, 300, 100), start));
+
+        let mut steps = vec![];
+        let mut now = start;
+        while !anims.is_empty() {
+            now += ms(10);
+            steps.extend(anims.tick(now).into_iter().map(|(_, step)| step));
+            assert!(now < start + Duration::from_secs(5), "animation never finished");
+        }
+
+        let resizes = steps.iter().filter(|step| matches!(step, FrameStep::Resize(_))).count();
+        assert_eq!(resizes, 1);
+        assert!(matches!(steps[0], FrameStep::Move(_)), "{steps:?}");
+        assert_eq!(steps.last(), Some(&FrameStep::Finish(rect(500, 0, 300, 100))));
+        let mut last_x = 0.0;
+        for step in &steps {
+            let x = match step {
+                FrameStep::Move(pos) => pos.x,
+                FrameStep::Resize(frame) | FrameStep::Finish(frame) => {
+                    assert_eq!(frame.size, CGSize::new(300., 100.));
+                    frame.origin.x
+                }
+            };
+            assert!(x >= last_x && x <= 500.0, "{steps:?}");
+            last_x = x;
+        }
+    }
+
+    #[test]
+    fn windows_that_only_move_are_not_resized() {
+        let start = Instant::now();
+        let mut anims = spring();
+        anims.animate(w(1), rect(0, 0, 100, 100), rect(0, 500, 100, 100), start);
+        let steps = anims.tick(start + ms(100));
+        assert!(matches!(steps[..], [(_, FrameStep::Move(_))]), "{steps:?}");
+        let steps = anims.tick(start + ms(5000));
+        assert_eq!(steps, [(w(1), FrameStep::Finish(rect(0, 500, 100, 100)))]);
+        assert!(anims.is_empty());
+    }
+
+    #[test]
+    fn retargeting_continues_from_current_position() {
+        let start = Instant::now();
+        let mut anims = spring();
+        anims.animate(w(1), rect(0, 0, 100, 100), rect(1000, 0, 100, 100), start);
+        let mid = start + ms(100);
+        let before = anims.current_frame(w(1), mid).unwrap();
+        assert!(before.origin.x > 0.0 && before.origin.x < 1000.0);
+
+        // The `from` frame is ignored for windows that are already animating.
+        assert!(!anims.animate(w(1), rect(0, 0, 100, 100), rect(0, 0, 100, 100), mid));
+        assert_eq!(anims.current_frame(w(1), mid), Some(before));
+
+        // The window keeps moving right for a bit before it turns around.
+        let after = anims.current_frame(w(1), mid + ms(10)).unwrap();
+        assert!(after.origin.x > before.origin.x, "{before:?} {after:?}");
+        let steps = anims.tick(mid + ms(5000));
+        assert_eq!(steps, [(w(1), FrameStep::Finish(rect(0, 0, 100, 100)))]);
+    }
+
+    #[test]
+    fn retargeting_to_a_new_size_resizes_again() {
+        let start = Instant::now();
+        let mut anims = spring();
+        anims.animate(w(1), rect(0, 0, 100, 100), rect(0, 0, 500, 100), start);
+        let steps = anims.tick(start + ms(200));
+        assert!(matches!(steps[..], [(_, FrameStep::Resize(_))]), "{steps:?}");
+
+        anims.animate(w(1), rect(0, 0, 0, 0), rect(0, 0, 100, 100), start + ms(200));
+        let mut now = start + ms(200);
+        let mut resized = false;
+        while !anims.is_empty() {
+            now += ms(10);
+            for (_, step) in anims.tick(now) {
+                resized |= matches!(step, FrameStep::Resize(frame) if frame.size.width == 100.0);
+            }
+        }
+        assert!(resized);
+    }
+
+    #[test]
+    fn easing_curves_finish_after_duration() {
+        let start = Instant::now();
+        for easing in [Easing::Linear, Easing::EaseOutCubic] {
+            let mut anims = animations(easing);
+            anims.animate(w(1), rect(0, 0, 100, 100), rect(1000, 0, 100, 100), start);
+            let x = anims.current_frame(w(1), start + ms(150)).unwrap().origin.x;
+            match easing {
+                Easing::Linear => assert_eq!(x, 500.0),
+                _ => assert_eq!(x, 875.0),
+            }
+            let steps = anims.tick(start + ms(299));
+            assert!(matches!(steps[..], [(_, FrameStep::Move(_))]), "{steps:?}");
+            let steps = anims.tick(start + ms(300));
+            assert_eq!(steps, [(w(1), FrameStep::Finish(rect(1000, 0, 100, 100)))]);
+        }
+    }
+
+    #[test]
+    fn retargeting_a_curve_restarts_it_from_the_current_position() {
+        let start = Instant::now();
+        let mut anims = animations(Easing::Linear);
+        anims.animate(w(1), rect(0, 0, 100, 100), rect(1000, 0, 100, 100), start);
+        anims.animate(w(1), rect(0, 0, 100, 100), rect(0, 0, 100, 100), start + ms(150));
+        assert_eq!(
+            anims.current_frame(w(1), start + ms(150)).unwrap().origin.x,
+            500.0
+        );
+        assert_eq!(
+            anims.current_frame(w(1), start + ms(300)).unwrap().origin.x,
+            250.0
+        );
+        let steps = anims.tick(start + ms(450));
+        assert_eq!(steps, [(w(1), FrameStep::Finish(rect(0, 0, 100, 100)))]);
+    }
+
+    #[test]
+    fn cancel() {
+        let start = Instant::now();
+        let mut anims = spring();
+        anims.animate(w(1), rect(0, 0, 100, 100), rect(100, 0, 100, 100), start);
+        anims.animate(w(2), rect(0, 0, 100, 100), rect(100, 0, 100, 100), start);
+        assert!(anims.cancel(w(1)));
+        assert!(!anims.cancel(w(1)));
+        assert!(!anims.is_animating(w(1)));
+        assert!(anims.is_animating(w(2)));
+        assert_eq!(anims.tick(start + ms(5000)).len(), 1);
+    }
+}
@@ -7,7 +7,9 @@ use std::fmt::{Debug, Formatter};
 
 use accessibility::{AXAttribute, AXUIElement, AXUIElementAttributes};
 pub use accessibility_sys::pid_t;
-use accessibility_sys::{kAXStandardWindowSubrole, kAXWindowRole};
+use accessibility_sys::{
+    kAXCloseButtonAttribute, kAXMinimizedAttribute, kAXStandardWindowSubrole, kAXWindowRole,
+};
 use core_foundation::base::{CFType, TCFType};
 use core_foundation::boolean::CFBoolean;
 use core_foundation::string::CFString;
@@ -122,6 +124,10 @@ pub trait AXUIElementExt {
     fn enhanced_user_interface(&self) -> Result<bool, accessibility::Error>;
     fn set_enhanced_user_interface(&self, enabled: bool) -> Result<(), accessibility::Error>;
 
+    /// The close button in the title bar of a window.
+    fn close_button(&self) -> Result<AXUIElement, accessibility::Error>;
+    fn set_minimized(&self, minimized: bool) -> Result<(), accessibility::Error>;
+
     fn privacy_sensitive_inspect(&self) -> Inspect<'_>;
 }
 
@@ -133,6 +139,16 @@ impl AXUIElementExt for AXUIElement {
         self.set_attribute(&enhanced_ui(), CFBoolean::from(enabled).as_CFType())
     }
 
+    fn close_button(&self) -> Result<AXUIElement, accessibility::Error> {
+        self.attribute(&AXAttribute::new(&CFString::from_static_string(
+            kAXCloseButtonAttribute,
+        )))
+    }
+    fn set_minimized(&self, minimized: bool) -> Result<(), accessibility::Error> {
+        let minimized_attr = AXAttribute::new(&CFString::from_static_string(kAXMinimizedAttribute));
+        self.set_attribute(&minimized_attr, CFBoolean::from(minimized).as_CFType())
+    }
+
     fn privacy_sensitive_inspect(&self) -> Inspect<'_> {
         Inspect(self)
     }
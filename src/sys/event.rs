@@ -2,6 +2,8 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 use core_graphics::base::CGError;
+use core_graphics::event::CGEvent;
+use core_graphics::event_source::{CGEventSource, CGEventSourceStateID};
 use livesplit_hotkey::{ConsumePreference, Hook};
 pub use livesplit_hotkey::{Hotkey, KeyCode, Modifiers};
 use objc2_app_kit::NSEvent;
@@ -12,6 +14,7 @@ use objc2_core_graphics::{
 use serde::{Deserialize, Serialize};
 use tracing::info_span;
 
+use super::geometry::ToICrate;
 use super::screen::CoordinateConverter;
 use crate::actor::reactor::Command;
 use crate::actor::wm_controller::{Sender, WmCommand, WmEvent};
@@ -65,6 +68,13 @@ pub fn get_mouse_pos(converter: CoordinateConverter) -> Option<CGPoint> {
     converter.convert_point(ns_loc)
 }
 
+/// Returns the position of the mouse in global display coordinates, which
+/// unlike [`get_mouse_pos`] doesn't need to know the screen layout.
+pub fn get_mouse_location() -> Option<CGPoint> {
+    let source = CGEventSource::new(CGEventSourceStateID::CombinedSessionState).ok()?;
+    Some(CGEvent::new(source).ok()?.location().to_icrate())
+}
+
 pub fn warp_mouse(point: CGPoint) -> Result<(), CGError> {
     cg_result(CGWarpMouseCursorPosition(point).0)
 }
@@ -244,6 +244,24 @@ impl<'de> DeserializeAs<'de, ic::CGRect> for CGRectDef {
     }
 }
 
+impl SerializeAs<ic::CGPoint> for CGPointDef {
+    fn serialize_as<S>(value: &ic::CGPoint, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        CGPointDef::serialize(value, serializer)
+    }
+}
+
+impl<'de> DeserializeAs<'de, ic::CGPoint> for CGPointDef {
+    fn deserialize_as<D>(deserializer: D) -> Result<ic::CGPoint, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        CGPointDef::deserialize(deserializer)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -281,30 +281,40 @@ impl NSScreenExt for NSScreen {
 ///
 /// Note: This relies on private APIs and might break.
 pub fn get_active_space_number() -> Option<usize> {
+    let active_id = unsafe { CGSGetActiveSpace(CGSMainConnectionID()) };
+    let active = SpaceId(NonZeroU64::new(active_id)?);
+    let position = managed_spaces().iter().position(|&space| space == Some(active))?;
+    Some(position + 1)
+}
+
+/// Returns every space in the order they are numbered for the user, or `None`
+/// for spaces whose id can't be read. Stops at a screen whose spaces can't be
+/// read, since the spaces after it can't be numbered.
+///
+/// Note: This relies on private APIs and might break.
+pub fn managed_spaces() -> Vec<Option<SpaceId>> {
     let cid = unsafe { CGSMainConnectionID() };
-    let space_info = unsafe { Retained::from_raw(CGSCopyManagedDisplaySpaces(cid))? };
-    let active_id = unsafe { CGSGetActiveSpace(cid) };
-    let mut count = 0;
+    let Some(space_info) = (unsafe { Retained::from_raw(CGSCopyManagedDisplaySpaces(cid)) }) else {
+        return vec![];
+    };
+    let mut ids = vec![];
     for screen in space_info {
-        let screen: Retained<NSDictionary> = screen.downcast().ok()?;
-        let spaces: Retained<NSArray> =
-            screen.valueForKey(ns_string!("Spaces"))?.downcast().ok()?;
+        let Some(spaces) = (|| {
+            let screen: Retained<NSDictionary> = screen.downcast().ok()?;
+            screen.valueForKey(ns_string!("Spaces"))?.downcast::<NSArray>().ok()
+        })() else {
+            break;
+        };
         for space in spaces {
-            count += 1;
-            let Some(id) = (|| {
+            ids.push((|| {
                 let space: Retained<NSDictionary> = space.downcast().ok()?;
                 let id: Retained<NSNumber> =
                     space.valueForKey(ns_string!("ManagedSpaceID"))?.downcast().ok()?;
-                Some(id)
-            })() else {
-                continue;
-            };
-            if id.as_u64() == active_id {
-                return Some(count);
-            }
+                Some(SpaceId(NonZeroU64::new(id.as_u64())?))
+            })());
         }
     }
-    None
+    ids
 }
 
 /// Utilities for querying the current system configuration. For diagnostic purposes only.